@@ -11,6 +11,6 @@ pub fn main() -> Result<()> {
     for arg in env::args().skip(1) {
         log::info!("{arg}");
     }
-    ToastLogger::flush()?;
+    ToastLogger::instance()?.flush()?;
     Ok(())
 }
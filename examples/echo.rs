@@ -5,6 +5,7 @@ use toast_logger_win::{Result, ToastLogger};
 pub fn main() -> Result<()> {
     ToastLogger::builder()
         .max_level(log::LevelFilter::Info)
+        .install_panic_hook(true)
         .init()?;
 
     let args: Vec<String> = env::args().skip(1).collect();
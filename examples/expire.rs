@@ -1,6 +1,6 @@
 use std::{env, time::Duration};
 
-use toast_logger_win::{Notification, ToastLogger};
+use toast_logger_win::ToastLogger;
 
 pub fn main() -> anyhow::Result<()> {
     let args: Vec<String> = env::args().skip(1).collect();
@@ -14,13 +14,8 @@ pub fn main() -> anyhow::Result<()> {
     let message = if duration.is_zero() {
         "This message shouldn't expire".into()
     } else {
-        let message = format!("This message should expire in {duration:?}.");
-        builder.create_notification(move |records| {
-            let mut notification = Notification::new_with_records(records)?;
-            notification.expires_in(duration)?;
-            Ok(notification)
-        });
-        message
+        builder.default_expiration(log::Level::Info, duration);
+        format!("This message should expire in {duration:?}.")
     };
     builder.max_level(log::LevelFilter::Info).init()?;
 
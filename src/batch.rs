@@ -0,0 +1,169 @@
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::{Arc, Condvar, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{Error, Result, sync::IgnorePoison};
+
+#[cfg(doc)]
+use crate::ToastLoggerBuilder;
+
+/// A timer thread that flushes buffered records
+/// after a quiet period, or after the maximum latency.
+///
+/// Please see [`ToastLoggerBuilder::batch()`].
+pub(crate) struct Batcher {
+    shared: Arc<(Mutex<BatchState>, Condvar)>,
+}
+
+#[derive(Debug, Default)]
+struct BatchState {
+    first: Option<Instant>,
+    last: Option<Instant>,
+    is_stopped: bool,
+}
+
+impl BatchState {
+    /// The time to flush, or `None` if nothing is buffered.
+    fn deadline(&self, quiet: Duration, max_latency: Duration) -> Option<Instant> {
+        let first = self.first?;
+        let last = self.last.unwrap_or(first);
+        Some((last + quiet).min(first + max_latency))
+    }
+}
+
+impl Batcher {
+    /// Start the timer thread.
+    /// The `flush` is called on the timer thread at each deadline.
+    /// The thread stops when the `flush` returns `false`,
+    /// or when the `Batcher` is dropped.
+    /// It continues when the `flush` panics.
+    ///
+    /// Returns [`Error::SpawnThread`] if the thread can't be spawned.
+    pub fn new<F>(quiet: Duration, max_latency: Duration, flush: F) -> Result<Self>
+    where
+        F: Fn() -> bool + Send + 'static,
+    {
+        let shared = Arc::new((Mutex::new(BatchState::default()), Condvar::new()));
+        let thread_shared = shared.clone();
+        thread::Builder::new()
+            .name("toast-logger-batch".into())
+            .spawn(move || Self::run(&thread_shared, quiet, max_latency, flush))
+            .map_err(Error::SpawnThread)?;
+        Ok(Self { shared })
+    }
+
+    /// Notify that a record was buffered.
+    pub fn notify(&self) {
+        let (state, condvar) = &*self.shared;
+        let mut state = state.lock().ignore_poison();
+        let now = Instant::now();
+        state.first.get_or_insert(now);
+        state.last = Some(now);
+        condvar.notify_one();
+    }
+
+    fn run<F>(
+        shared: &(Mutex<BatchState>, Condvar),
+        quiet: Duration,
+        max_latency: Duration,
+        flush: F,
+    ) where
+        F: Fn() -> bool,
+    {
+        let (state, condvar) = shared;
+        let mut state = state.lock().ignore_poison();
+        while !state.is_stopped {
+            let Some(deadline) = state.deadline(quiet, max_latency) else {
+                state = condvar.wait(state).ignore_poison();
+                continue;
+            };
+            let now = Instant::now();
+            if now < deadline {
+                state = condvar
+                    .wait_timeout(state, deadline - now)
+                    .ignore_poison()
+                    .0;
+                continue;
+            }
+            state.first = None;
+            state.last = None;
+            drop(state);
+            // The panic is reported by the panic hook.
+            if let Ok(false) = panic::catch_unwind(AssertUnwindSafe(&flush)) {
+                return;
+            }
+            state = shared.0.lock().ignore_poison();
+        }
+    }
+}
+
+impl Drop for Batcher {
+    fn drop(&mut self) {
+        let (state, condvar) = &*self.shared;
+        state.lock().ignore_poison().is_stopped = true;
+        condvar.notify_one();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc,
+    };
+
+    use super::*;
+
+    #[test]
+    fn deadline() {
+        let quiet = Duration::from_millis(200);
+        let max_latency = Duration::from_secs(1);
+        let mut state = BatchState::default();
+        assert_eq!(state.deadline(quiet, max_latency), None);
+
+        let first = Instant::now();
+        state.first = Some(first);
+        state.last = Some(first);
+        assert_eq!(state.deadline(quiet, max_latency), Some(first + quiet));
+
+        state.last = Some(first + Duration::from_millis(900));
+        assert_eq!(
+            state.deadline(quiet, max_latency),
+            Some(first + max_latency)
+        );
+    }
+
+    #[test]
+    fn flush_once_after_burst() -> Result<()> {
+        let (sender, receiver) = mpsc::channel();
+        let batcher = Batcher::new(
+            Duration::from_millis(50),
+            Duration::from_secs(10),
+            move || sender.send(()).is_ok(),
+        )?;
+        for _ in 0..10 {
+            batcher.notify();
+        }
+        assert!(receiver.recv_timeout(Duration::from_secs(5)).is_ok());
+        assert!(receiver.recv_timeout(Duration::from_millis(200)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn panic() -> Result<()> {
+        let (sender, receiver) = mpsc::channel();
+        let is_first = AtomicBool::new(true);
+        let batcher = Batcher::new(Duration::ZERO, Duration::ZERO, move || {
+            assert!(!is_first.swap(false, Ordering::Relaxed), "first flush");
+            sender.send(()).is_ok()
+        })?;
+        batcher.notify();
+        thread::sleep(Duration::from_millis(100));
+        batcher.notify();
+        assert!(receiver.recv_timeout(Duration::from_secs(5)).is_ok());
+        Ok(())
+    }
+}
@@ -0,0 +1,75 @@
+use std::collections::VecDeque;
+
+use crate::BufferedRecord;
+
+#[cfg(doc)]
+use crate::ToastLoggerBuilder;
+
+/// A bounded buffer of records below the trigger level,
+/// kept as the context of the next record at or above the trigger level.
+///
+/// Please see [`ToastLoggerBuilder::breadcrumbs()`].
+#[derive(Debug)]
+pub(crate) struct Breadcrumbs {
+    trigger: log::Level,
+    capacity: usize,
+    records: VecDeque<BufferedRecord>,
+}
+
+impl Breadcrumbs {
+    pub fn new(trigger: log::Level, capacity: usize) -> Self {
+        Self {
+            trigger,
+            capacity,
+            records: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Add the `record`.
+    /// Returns the records to show
+    /// if the `record` is at or above the trigger level.
+    pub fn push(&mut self, record: BufferedRecord) -> Option<Vec<BufferedRecord>> {
+        if record.level <= self.trigger {
+            let mut records: Vec<_> = self.records.drain(..).collect();
+            records.push(record);
+            return Some(records);
+        }
+        if self.capacity == 0 {
+            return None;
+        }
+        if self.records.len() >= self.capacity {
+            self.records.pop_front();
+        }
+        self.records.push_back(record);
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(level: log::Level, args: &str) -> BufferedRecord {
+        BufferedRecord::new(level, "", args)
+    }
+
+    #[test]
+    fn push() {
+        let mut breadcrumbs = Breadcrumbs::new(log::Level::Error, 2);
+        assert_eq!(breadcrumbs.push(record(log::Level::Info, "1")), None);
+        assert_eq!(breadcrumbs.push(record(log::Level::Info, "2")), None);
+        assert_eq!(breadcrumbs.push(record(log::Level::Warn, "3")), None);
+        assert_eq!(
+            breadcrumbs.push(record(log::Level::Error, "4")),
+            Some(vec![
+                record(log::Level::Info, "2"),
+                record(log::Level::Warn, "3"),
+                record(log::Level::Error, "4"),
+            ])
+        );
+        assert_eq!(
+            breadcrumbs.push(record(log::Level::Error, "5")),
+            Some(vec![record(log::Level::Error, "5")])
+        );
+    }
+}
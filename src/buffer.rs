@@ -0,0 +1,168 @@
+use std::{collections::VecDeque, mem};
+
+use crate::BufferedRecord;
+
+#[cfg(doc)]
+use crate::ToastLoggerBuilder;
+
+/// What to do when the buffer is full.
+///
+/// Please see [`ToastLoggerBuilder::buffer_capacity()`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Drop the oldest record to make room for the new record.
+    #[default]
+    DropOldest,
+    /// Drop the new record.
+    DropNewest,
+    /// Show the buffered records, and start a new buffer.
+    Flush,
+}
+
+/// The buffer of records to show on the next flush.
+#[derive(Debug, Default)]
+pub(crate) struct RecordBuffer {
+    records: VecDeque<BufferedRecord>,
+    capacity: Option<usize>,
+    policy: OverflowPolicy,
+    is_dedup: bool,
+    dropped: usize,
+    dropped_level: Option<log::Level>,
+    /// The total number since created, for [`crate::Stats`].
+    total_dropped: u64,
+}
+
+impl RecordBuffer {
+    pub fn new(capacity: Option<usize>, policy: OverflowPolicy, is_dedup: bool) -> Self {
+        Self {
+            capacity: capacity.map(|capacity| capacity.max(1)),
+            policy,
+            is_dedup,
+            ..Default::default()
+        }
+    }
+
+    /// Append the `record`.
+    ///
+    /// Returns the records to show now
+    /// if the buffer is full and the policy is [`OverflowPolicy::Flush`].
+    pub fn push(&mut self, record: BufferedRecord) -> Option<Vec<BufferedRecord>> {
+        if self.is_dedup
+            && let Some(existing) = self.records.iter_mut().find(|r| **r == record)
+        {
+            existing.merge(&record);
+            return None;
+        }
+        if self
+            .capacity
+            .is_none_or(|capacity| self.records.len() < capacity)
+        {
+            self.records.push_back(record);
+            return None;
+        }
+        match self.policy {
+            OverflowPolicy::DropOldest => {
+                if let Some(oldest) = self.records.pop_front() {
+                    self.drop_record(&oldest);
+                }
+                self.records.push_back(record);
+                None
+            }
+            OverflowPolicy::DropNewest => {
+                self.drop_record(&record);
+                None
+            }
+            OverflowPolicy::Flush => {
+                let records = self.take();
+                self.records.push_back(record);
+                records
+            }
+        }
+    }
+
+    /// Take all the records,
+    /// appending the number of dropped records if any.
+    pub fn take(&mut self) -> Option<Vec<BufferedRecord>> {
+        if self.records.is_empty() && self.dropped_level.is_none() {
+            return None;
+        }
+        let mut records = Vec::from(mem::take(&mut self.records));
+        if let Some(level) = self.dropped_level.take() {
+            let count = mem::take(&mut self.dropped);
+            let noun = if count == 1 { "message" } else { "messages" };
+            let args = format!("\u{2026}and {count} more {noun} dropped");
+            records.push(BufferedRecord::new_summary(level, &args));
+        }
+        Some(records)
+    }
+
+    /// The total number of records dropped by overflows.
+    pub fn total_dropped(&self) -> u64 {
+        self.total_dropped
+    }
+
+    fn drop_record(&mut self, record: &BufferedRecord) {
+        self.dropped += record.count;
+        self.total_dropped += record.count as u64;
+        self.dropped_level = Some(
+            self.dropped_level
+                .map_or(record.level, |level| level.min(record.level)),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push_all(buffer: &mut RecordBuffer, args: &[&str]) -> Vec<Vec<BufferedRecord>> {
+        args.iter()
+            .filter_map(|args| buffer.push(BufferedRecord::new(log::Level::Info, "", args)))
+            .collect()
+    }
+
+    fn args(records: &[BufferedRecord]) -> Vec<&str> {
+        records.iter().map(|r| r.args()).collect()
+    }
+
+    #[test]
+    fn drop_oldest() {
+        let mut buffer = RecordBuffer::new(Some(2), OverflowPolicy::DropOldest, false);
+        assert!(push_all(&mut buffer, &["1", "2", "3", "4"]).is_empty());
+        assert_eq!(
+            args(&buffer.take().unwrap()),
+            ["3", "4", "\u{2026}and 2 more messages dropped"]
+        );
+        assert_eq!(buffer.take(), None);
+        assert_eq!(buffer.total_dropped(), 2);
+    }
+
+    #[test]
+    fn drop_newest() {
+        let mut buffer = RecordBuffer::new(Some(2), OverflowPolicy::DropNewest, false);
+        assert!(push_all(&mut buffer, &["1", "2", "3"]).is_empty());
+        assert_eq!(
+            args(&buffer.take().unwrap()),
+            ["1", "2", "\u{2026}and 1 more message dropped"]
+        );
+    }
+
+    #[test]
+    fn flush() {
+        let mut buffer = RecordBuffer::new(Some(2), OverflowPolicy::Flush, false);
+        let flushed = push_all(&mut buffer, &["1", "2", "3"]);
+        assert_eq!(flushed.len(), 1);
+        assert_eq!(args(&flushed[0]), ["1", "2"]);
+        assert_eq!(args(&buffer.take().unwrap()), ["3"]);
+    }
+
+    #[test]
+    fn dedup() {
+        let mut buffer = RecordBuffer::new(Some(1), OverflowPolicy::DropNewest, true);
+        assert!(push_all(&mut buffer, &["1", "1", "1"]).is_empty());
+        let records = buffer.take().unwrap();
+        assert_eq!(args(&records), ["1"]);
+        assert_eq!(records[0].count, 3);
+        assert_eq!(buffer.total_dropped(), 0);
+    }
+}
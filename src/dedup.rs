@@ -0,0 +1,155 @@
+use std::time::{Duration, SystemTime};
+
+use crate::BufferedRecord;
+
+#[cfg(doc)]
+use crate::ToastLoggerBuilder;
+
+/// Deduplicates records repeated within a time window.
+///
+/// Please see [`ToastLoggerBuilder::dedup()`].
+#[derive(Debug)]
+pub(crate) struct Deduplicator {
+    window: Duration,
+    /// The records shown recently.
+    /// The `count` and the timestamps are of the suppressed repetitions.
+    entries: Vec<Entry>,
+}
+
+#[derive(Debug)]
+struct Entry {
+    shown_time: SystemTime,
+    suppressed: Option<BufferedRecord>,
+    record: BufferedRecord,
+}
+
+impl Deduplicator {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Returns the `record` to show,
+    /// or `None` if it was shown within the window.
+    ///
+    /// The repetitions within the window are merged
+    /// into the next record shown after the window.
+    pub fn push(&mut self, mut record: BufferedRecord) -> Option<BufferedRecord> {
+        let now = record.last_time;
+        let Some(entry) = self.entries.iter_mut().find(|e| e.record == record) else {
+            self.entries.push(Entry {
+                shown_time: now,
+                suppressed: None,
+                record: record.clone(),
+            });
+            return Some(record);
+        };
+
+        if Self::is_in_window(self.window, entry.shown_time, now) {
+            match &mut entry.suppressed {
+                Some(suppressed) => suppressed.merge(&record),
+                None => entry.suppressed = Some(record),
+            }
+            return None;
+        }
+        if let Some(suppressed) = entry.suppressed.take() {
+            record.merge(&suppressed);
+        }
+        entry.shown_time = now;
+        Some(record)
+    }
+
+    /// Remove the records whose windows ended before `now`,
+    /// and returns their repetitions not shown yet.
+    pub fn take_expired(&mut self, now: SystemTime) -> Vec<BufferedRecord> {
+        let mut expired = Vec::new();
+        self.entries.retain_mut(|entry| {
+            if Self::is_in_window(self.window, entry.shown_time, now) {
+                return true;
+            }
+            expired.extend(entry.suppressed.take());
+            false
+        });
+        expired
+    }
+
+    /// Returns the repetitions not shown yet.
+    /// Their windows continue, so later repetitions are still counted.
+    pub fn take_suppressed(&mut self) -> Vec<BufferedRecord> {
+        self.entries
+            .iter_mut()
+            .filter_map(|entry| entry.suppressed.take())
+            .collect()
+    }
+
+    fn is_in_window(window: Duration, time: SystemTime, now: SystemTime) -> bool {
+        now.duration_since(time)
+            .is_ok_and(|elapsed| elapsed < window)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(args: &str, time: SystemTime) -> BufferedRecord {
+        let mut record = BufferedRecord::new(log::Level::Error, "", args);
+        record.first_time = time;
+        record.last_time = time;
+        record
+    }
+
+    #[test]
+    fn push() {
+        let start = SystemTime::UNIX_EPOCH;
+        let secs = |secs| start + Duration::from_secs(secs);
+        let mut dedup = Deduplicator::new(Duration::from_secs(10));
+        assert!(dedup.push(record("a", secs(0))).is_some());
+        assert!(dedup.push(record("b", secs(1))).is_some());
+        assert!(dedup.push(record("a", secs(2))).is_none());
+        assert!(dedup.push(record("a", secs(3))).is_none());
+
+        let shown = dedup.push(record("a", secs(10))).unwrap();
+        assert_eq!(shown.count, 3);
+        assert_eq!(shown.first_time, secs(2));
+        assert_eq!(shown.last_time, secs(10));
+        assert!(dedup.push(record("a", secs(11))).is_none());
+    }
+
+    #[test]
+    fn take_expired() {
+        let start = SystemTime::UNIX_EPOCH;
+        let secs = |secs| start + Duration::from_secs(secs);
+        let mut dedup = Deduplicator::new(Duration::from_secs(10));
+        assert!(dedup.push(record("a", secs(0))).is_some());
+        assert!(dedup.push(record("a", secs(1))).is_none());
+        assert!(dedup.push(record("b", secs(5))).is_some());
+        assert!(dedup.take_expired(secs(9)).is_empty());
+
+        let expired = dedup.take_expired(secs(10));
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].args, "a");
+        assert_eq!(expired[0].count, 1);
+        assert_eq!(dedup.entries.len(), 1);
+        assert!(dedup.take_expired(secs(15)).is_empty());
+        assert!(dedup.entries.is_empty());
+    }
+
+    #[test]
+    fn take_suppressed() {
+        let start = SystemTime::UNIX_EPOCH;
+        let secs = |secs| start + Duration::from_secs(secs);
+        let mut dedup = Deduplicator::new(Duration::from_secs(10));
+        assert!(dedup.push(record("a", secs(0))).is_some());
+        assert!(dedup.push(record("a", secs(1))).is_none());
+        assert!(dedup.push(record("a", secs(2))).is_none());
+        let suppressed = dedup.take_suppressed();
+        assert_eq!(suppressed.len(), 1);
+        assert_eq!(suppressed[0].count, 2);
+        assert!(dedup.take_suppressed().is_empty());
+        // The window continues after taking.
+        assert!(dedup.push(record("a", secs(3))).is_none());
+    }
+}
@@ -1,21 +1,73 @@
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
+    #[error("ToastLogger already initialized")]
+    AlreadyInitialized,
+
     #[error(transparent)]
     Format(#[from] std::fmt::Error),
 
+    #[error("The tag is empty")]
+    EmptyTag,
+
+    #[error("The expiration time is in the past")]
+    ExpirationInPast,
+
+    #[error("The expiration time is out of range")]
+    ExpirationOutOfRange,
+
+    #[error("Timed out after {0:?} waiting for notifications to be shown")]
+    FlushTimeout(std::time::Duration),
+
+    #[error("Invalid value of the environment variable {0}: {1:?}")]
+    InvalidEnv(String, String),
+
+    #[error("Invalid filter: {0}")]
+    InvalidFilter(String),
+
+    #[error("Invalid schedule: {0}")]
+    InvalidSchedule(&'static str),
+
     #[error("ToastLogger not initialized")]
     NotInitialized,
 
     #[error(transparent)]
     SetLogger(#[from] log::SetLoggerError),
 
-    #[cfg(not(feature = "winrt-toast"))]
+    #[error("Failed to spawn a thread: {0}")]
+    SpawnThread(std::io::Error),
+
+    #[cfg(all(windows, not(feature = "winrt-toast")))]
     #[error("Windows Error: {0}")]
     Windows(#[from] windows::core::Error),
 
-    #[cfg(feature = "winrt-toast")]
+    #[cfg(all(windows, feature = "winrt-toast"))]
+    #[error("`{0}` is not supported with the `winrt-toast` feature")]
+    NotSupported(&'static str),
+
+    #[cfg(all(windows, feature = "winrt-toast"))]
     #[error("winrt_toast Error: {0}")]
     WinToast(#[from] winrt_toast::WinToastError),
+
+    #[cfg(not(windows))]
+    #[error("Toast notifications are supported only on Windows")]
+    UnsupportedPlatform,
+}
+
+impl Error {
+    /// Whether the operation may succeed when retried,
+    /// such as when the notification platform is temporarily unavailable.
+    ///
+    /// Please see [`ToastLoggerBuilder::retry()`].
+    ///
+    /// [`ToastLoggerBuilder::retry()`]: crate::ToastLoggerBuilder::retry()
+    pub fn is_transient(&self) -> bool {
+        #[cfg(all(windows, not(feature = "winrt-toast")))]
+        if let Self::Windows(error) = self {
+            return crate::win::is_transient(error);
+        }
+        // The `winrt-toast` errors don't expose the `HRESULT`s.
+        false
+    }
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,107 @@
+use std::{collections::HashMap, sync::Arc};
+
+use crate::{BufferedRecord, Error};
+
+#[cfg(doc)]
+use crate::ToastLoggerBuilder;
+
+/// The reason why a notification was dismissed.
+/// Please see [`ToastDismissalReason`].
+///
+/// [`ToastDismissalReason`]: https://learn.microsoft.com/uwp/api/windows.ui.notifications.toastdismissalreason
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DismissalReason {
+    /// The user dismissed the notification.
+    UserCanceled,
+    /// The application hid the notification.
+    ApplicationHidden,
+    /// The notification has timed out.
+    TimedOut,
+}
+
+type ActivatedHandler =
+    dyn Fn(&str, &HashMap<String, String>, &[BufferedRecord]) + Send + Sync + 'static;
+type DismissedHandler = dyn Fn(DismissalReason, &[BufferedRecord]) + Send + Sync + 'static;
+type FailedHandler = dyn Fn(&Error, &[BufferedRecord]) + Send + Sync + 'static;
+
+/// The event handlers of a notification,
+/// bound to the records the notification was created from.
+///
+/// Please see [`ToastLoggerBuilder::on_activated()`] for how to set the handlers.
+#[derive(Clone, Default)]
+pub(crate) struct NotificationEvents {
+    records: Arc<[BufferedRecord]>,
+    on_activated: Option<Arc<ActivatedHandler>>,
+    on_dismissed: Option<Arc<DismissedHandler>>,
+    on_failed: Option<Arc<FailedHandler>>,
+}
+
+// The `winrt-toast` crate and other platforms don't support events.
+#[cfg_attr(not(all(windows, not(feature = "winrt-toast"))), allow(dead_code))]
+impl NotificationEvents {
+    pub fn set_on_activated<F>(&mut self, handler: F)
+    where
+        F: Fn(&str, &HashMap<String, String>, &[BufferedRecord]) + Send + Sync + 'static,
+    {
+        self.on_activated = Some(Arc::new(handler));
+    }
+
+    pub fn set_on_dismissed<F>(&mut self, handler: F)
+    where
+        F: Fn(DismissalReason, &[BufferedRecord]) + Send + Sync + 'static,
+    {
+        self.on_dismissed = Some(Arc::new(handler));
+    }
+
+    pub fn set_on_failed<F>(&mut self, handler: F)
+    where
+        F: Fn(&Error, &[BufferedRecord]) + Send + Sync + 'static,
+    {
+        self.on_failed = Some(Arc::new(handler));
+    }
+
+    /// Clone the handlers, bound to the `records`.
+    pub fn with_records(&self, records: &[BufferedRecord]) -> Self {
+        if self.is_empty() {
+            return Self::default();
+        }
+        Self {
+            records: records.iter().cloned().collect(),
+            ..self.clone()
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.on_activated.is_none() && self.on_dismissed.is_none() && self.on_failed.is_none()
+    }
+
+    pub fn has_activated(&self) -> bool {
+        self.on_activated.is_some()
+    }
+
+    pub fn has_dismissed(&self) -> bool {
+        self.on_dismissed.is_some()
+    }
+
+    pub fn has_failed(&self) -> bool {
+        self.on_failed.is_some()
+    }
+
+    pub fn activated(&self, arguments: &str, inputs: &HashMap<String, String>) {
+        if let Some(handler) = &self.on_activated {
+            handler(arguments, inputs, &self.records);
+        }
+    }
+
+    pub fn dismissed(&self, reason: DismissalReason) {
+        if let Some(handler) = &self.on_dismissed {
+            handler(reason, &self.records);
+        }
+    }
+
+    pub fn failed(&self, error: &Error) {
+        if let Some(handler) = &self.on_failed {
+            handler(error, &self.records);
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use std::sync::{
+    OnceLock,
+    atomic::{AtomicBool, Ordering},
+};
+
+#[cfg(doc)]
+use crate::ToastLoggerBuilder;
+
+/// The function to call on exit.
+/// Please see [`ToastLoggerBuilder::flush_on_exit()`].
+static CALLBACK: OnceLock<fn()> = OnceLock::new();
+static IS_CALLED: AtomicBool = AtomicBool::new(false);
+
+unsafe extern "C" {
+    fn atexit(callback: extern "C" fn()) -> std::ffi::c_int;
+}
+
+/// Call the `callback` when the process exits,
+/// including by [`std::process::exit()`].
+pub(crate) fn call_at_exit(callback: fn()) {
+    CALLBACK.get_or_init(|| callback);
+    // SAFETY: `on_exit` is a valid function for the whole process lifetime.
+    if unsafe { atexit(on_exit) } != 0 {
+        eprintln!("Failed to register the exit handler");
+    }
+}
+
+/// Call the `callback` on Ctrl+C, Ctrl+Break,
+/// closing the console, logoff, and shutdown.
+pub(crate) fn call_on_console_events(callback: fn()) {
+    CALLBACK.get_or_init(|| callback);
+    #[cfg(windows)]
+    console::register();
+}
+
+/// Call the callback once, even when multiple events occur.
+fn call_once() {
+    if IS_CALLED.swap(true, Ordering::AcqRel) {
+        return;
+    }
+    if let Some(callback) = CALLBACK.get() {
+        callback();
+    }
+}
+
+extern "C" fn on_exit() {
+    call_once();
+}
+
+#[cfg(windows)]
+mod console {
+    // Declared here rather than using the `windows` crate,
+    // so that this works with the `winrt-toast` feature too.
+    #[link(name = "kernel32")]
+    unsafe extern "system" {
+        fn SetConsoleCtrlHandler(
+            handler: Option<unsafe extern "system" fn(u32) -> i32>,
+            add: i32,
+        ) -> i32;
+    }
+
+    pub fn register() {
+        // SAFETY: `handler` is a valid function for the whole process lifetime.
+        if unsafe { SetConsoleCtrlHandler(Some(handler), 1) } == 0 {
+            eprintln!("Failed to register the console control handler");
+        }
+    }
+
+    /// Returns `FALSE` so that the next handler,
+    /// usually the default one that exits the process, is called.
+    unsafe extern "system" fn handler(_ctrl_type: u32) -> i32 {
+        super::call_once();
+        0
+    }
+}
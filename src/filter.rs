@@ -0,0 +1,254 @@
+use std::str::FromStr;
+
+use crate::{Error, Result};
+
+#[cfg(doc)]
+use crate::ToastLoggerBuilder;
+
+/// A filter of log records by their targets and messages.
+///
+/// Please see [`ToastLoggerBuilder::parse_filters()`] for the syntax.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Filter {
+    default_level: log::LevelFilter,
+    /// Sorted by the length of the targets, longest first.
+    directives: Vec<Directive>,
+    message: Option<MessageFilter>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Directive {
+    target: String,
+    level: log::LevelFilter,
+}
+
+/// A filter of messages, a regular expression with the feature `regex`,
+/// or a substring without it.
+#[derive(Clone, Debug)]
+struct MessageFilter {
+    #[cfg(feature = "regex")]
+    regex: regex::Regex,
+    #[cfg(not(feature = "regex"))]
+    text: String,
+}
+
+impl MessageFilter {
+    fn new(message: &str) -> Result<Self> {
+        Ok(Self {
+            #[cfg(feature = "regex")]
+            regex: regex::Regex::new(message)
+                .map_err(|error| Error::InvalidFilter(format!("{message}: {error}")))?,
+            #[cfg(not(feature = "regex"))]
+            text: message.into(),
+        })
+    }
+
+    fn as_str(&self) -> &str {
+        #[cfg(feature = "regex")]
+        {
+            self.regex.as_str()
+        }
+        #[cfg(not(feature = "regex"))]
+        {
+            &self.text
+        }
+    }
+
+    fn is_match(&self, message: &str) -> bool {
+        #[cfg(feature = "regex")]
+        {
+            self.regex.is_match(message)
+        }
+        #[cfg(not(feature = "regex"))]
+        {
+            message.contains(self.text.as_str())
+        }
+    }
+}
+
+impl PartialEq for MessageFilter {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for MessageFilter {}
+
+impl Filter {
+    pub fn new(default_level: log::LevelFilter) -> Self {
+        Self {
+            default_level,
+            directives: Vec::new(),
+            message: None,
+        }
+    }
+
+    /// Set the level for the targets without matching directives.
+    pub fn set_default_level(&mut self, level: log::LevelFilter) {
+        self.default_level = level;
+    }
+
+    /// Set the level for the targets starting with the `target`.
+    pub fn set_target_level(&mut self, target: &str, level: log::LevelFilter) {
+        if let Some(directive) = self.directives.iter_mut().find(|d| d.target == target) {
+            directive.level = level;
+            return;
+        }
+        let index = self
+            .directives
+            .partition_point(|d| d.target.len() >= target.len());
+        self.directives.insert(
+            index,
+            Directive {
+                target: target.into(),
+                level,
+            },
+        );
+    }
+
+    /// Parse the `filters` and merge them into this filter.
+    pub fn parse(&mut self, filters: &str) -> Result<()> {
+        let (directives, message) = match filters.split_once('/') {
+            Some((directives, message)) => (directives, Some(message)),
+            None => (filters, None),
+        };
+        for directive in directives.split(',').map(str::trim) {
+            if directive.is_empty() {
+                continue;
+            }
+            let mut parts = directive.split('=');
+            let (target, level) = match (parts.next(), parts.next(), parts.next()) {
+                (Some(target_or_level), None, None) => match parse_level(target_or_level) {
+                    Ok(level) => (None, level),
+                    Err(_) => (Some(target_or_level), log::LevelFilter::Trace),
+                },
+                (Some(target), Some(level), None) => (Some(target.trim()), parse_level(level)?),
+                _ => return Err(Error::InvalidFilter(directive.into())),
+            };
+            match target {
+                Some(target) => self.set_target_level(target, level),
+                None => self.set_default_level(level),
+            }
+        }
+        if let Some(message) = message {
+            self.message = Some(MessageFilter::new(message)?);
+        }
+        Ok(())
+    }
+
+    /// The maximum level of all directives,
+    /// suitable for [`log::set_max_level()`].
+    pub fn max_level(&self) -> log::LevelFilter {
+        self.directives
+            .iter()
+            .map(|d| d.level)
+            .fold(self.default_level, Ord::max)
+    }
+
+    /// Whether the `metadata` is enabled by the directives.
+    /// The directive with the longest matching target wins.
+    pub fn enabled(&self, metadata: &log::Metadata) -> bool {
+        let target = metadata.target();
+        let level = self
+            .directives
+            .iter()
+            .find(|d| target.starts_with(&d.target))
+            .map_or(self.default_level, |d| d.level);
+        metadata.level() <= level
+    }
+
+    /// Whether the message of the `record` matches the message filter.
+    pub fn matches(&self, record: &log::Record) -> bool {
+        match &self.message {
+            Some(message) => message.is_match(&record.args().to_string()),
+            None => true,
+        }
+    }
+}
+
+impl FromStr for Filter {
+    type Err = Error;
+
+    fn from_str(filters: &str) -> Result<Self> {
+        let mut filter = Self::new(log::LevelFilter::Error);
+        filter.parse(filters)?;
+        Ok(filter)
+    }
+}
+
+fn parse_level(level: &str) -> Result<log::LevelFilter> {
+    level
+        .trim()
+        .parse()
+        .map_err(|_| Error::InvalidFilter(level.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn enabled(filter: &Filter, target: &str, level: log::Level) -> bool {
+        let metadata = log::Metadata::builder().target(target).level(level).build();
+        filter.enabled(&metadata)
+    }
+
+    #[test]
+    fn parse() -> Result<()> {
+        let filter: Filter = "warn,my_app::net=info,hyper=off,my_app=debug".parse()?;
+        assert!(enabled(&filter, "other", log::Level::Warn));
+        assert!(!enabled(&filter, "other", log::Level::Info));
+        assert!(enabled(&filter, "my_app::net", log::Level::Info));
+        assert!(!enabled(&filter, "my_app::net", log::Level::Debug));
+        assert!(enabled(&filter, "my_app::ui", log::Level::Debug));
+        assert!(!enabled(&filter, "hyper::client", log::Level::Error));
+        assert_eq!(filter.max_level(), log::LevelFilter::Debug);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_target_only() -> Result<()> {
+        let filter: Filter = "my_app".parse()?;
+        assert!(enabled(&filter, "my_app", log::Level::Trace));
+        assert!(!enabled(&filter, "other", log::Level::Warn));
+        assert!(enabled(&filter, "other", log::Level::Error));
+        Ok(())
+    }
+
+    #[test]
+    fn parse_error() {
+        assert!("my_app=loud".parse::<Filter>().is_err());
+        assert!("my_app=info=warn".parse::<Filter>().is_err());
+    }
+
+    #[test]
+    fn message() -> Result<()> {
+        let filter: Filter = "info/lost".parse()?;
+        let matches = |message| {
+            filter.matches(
+                &log::Record::builder()
+                    .args(format_args!("{message}"))
+                    .build(),
+            )
+        };
+        assert!(matches("Connection lost"));
+        assert!(!matches("Connected"));
+        Ok(())
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn message_regex() -> Result<()> {
+        let filter: Filter = "info/^Conn.*(lost|closed)$".parse()?;
+        let matches = |message| {
+            filter.matches(
+                &log::Record::builder()
+                    .args(format_args!("{message}"))
+                    .build(),
+            )
+        };
+        assert!(matches("Connection closed"));
+        assert!(!matches("Reconnection lost"));
+        assert!("info/(".parse::<Filter>().is_err());
+        Ok(())
+    }
+}
@@ -23,16 +23,54 @@
 //!   the underlying implementation
 //!   from the [`windows` crate] to the [`winrt-toast` crate].
 //!   Please see the [`Notification`] for more details.
+//!   Some features are not supported by the `winrt-toast` crate
+//!   and return `Error::NotSupported` when it's enabled.
+//! * The feature `regex` enables regular expressions
+//!   in the message filters of `ToastLoggerBuilder::parse_filters()`.
+//!   Without it, the message filters match substrings.
+//!
+//! # Platforms
+//!
+//! The crate builds on other platforms than Windows,
+//! so that applications and tests can run on them,
+//! but showing notifications returns `Error::UnsupportedPlatform`.
 //!
 //! [`winrt-toast` crate]: https://docs.rs/winrt-toast/latest/winrt_toast/
 //! [`windows` crate]: https://crates.io/crates/windows
 
-#[cfg(not(feature = "winrt-toast"))]
+#[cfg(not(windows))]
+mod unsupported;
+#[cfg(all(windows, not(feature = "winrt-toast")))]
 pub(crate) mod win;
+#[cfg(all(windows, feature = "winrt-toast"))]
+pub(crate) mod winrt;
+#[cfg(not(windows))]
+use unsupported as win;
 
+mod batch;
+mod breadcrumbs;
+mod buffer;
+pub use buffer::*;
+mod dedup;
 mod error;
 pub use error::{Error, Result};
+mod events;
+pub use events::*;
+mod exit;
+mod filter;
+mod limits;
+#[cfg(test)]
+mod mock;
 mod notification;
 pub use notification::*;
+mod rate_limit;
+mod retry;
+pub use retry::*;
+mod schedule;
+pub use schedule::*;
+mod stats;
+pub use stats::*;
+mod sync;
 mod toast_logger;
 pub use toast_logger::*;
+mod worker;
@@ -0,0 +1,297 @@
+use crate::BufferedRecord;
+
+#[cfg(doc)]
+use crate::ToastLoggerBuilder;
+
+/// Limits of the content of a notification.
+///
+/// Please see [`ToastLoggerBuilder::max_records()`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct ContentLimits {
+    pub max_records: Option<usize>,
+    pub max_record_chars: Option<usize>,
+    pub max_chars: Option<usize>,
+    pub is_split: bool,
+}
+
+impl ContentLimits {
+    /// The number of characters reserved for the footer.
+    const FOOTER_CHARS: usize = 48;
+
+    pub fn is_unlimited(&self) -> bool {
+        self.max_records.is_none() && self.max_record_chars.is_none() && self.max_chars.is_none()
+    }
+
+    /// Split the `records` into notifications within the limits.
+    ///
+    /// Returns one notification with a footer of the omitted records,
+    /// or multiple notifications if [`ContentLimits::is_split`] is set.
+    /// The room for the footer is reserved only when records are omitted.
+    pub fn apply(&self, records: &[BufferedRecord]) -> Vec<Vec<BufferedRecord>> {
+        let truncated = self.truncate_records(records, 0);
+        if self.is_split {
+            let mut notifications = Vec::new();
+            let mut records = truncated;
+            loop {
+                let (selected, omitted) = self.select(records, 0);
+                notifications.push(selected);
+                if omitted.is_empty() {
+                    return notifications;
+                }
+                records = omitted;
+            }
+        }
+        let (selected, omitted) = self.select(truncated, 0);
+        if omitted.is_empty() {
+            return vec![selected];
+        }
+        // Retry with the room for the footer.
+        let truncated = self.truncate_records(records, Self::FOOTER_CHARS);
+        let (mut selected, omitted) = self.select(truncated, Self::FOOTER_CHARS);
+        if !omitted.is_empty() {
+            selected.push(Self::footer(&omitted));
+        }
+        vec![selected]
+    }
+
+    /// Truncate the `records` to fit in the limits
+    /// with the `reserved` characters.
+    fn truncate_records(&self, records: &[BufferedRecord], reserved: usize) -> Vec<BufferedRecord> {
+        records
+            .iter()
+            .map(|record| self.truncate_record(record, reserved))
+            .collect()
+    }
+
+    fn truncate_record(&self, record: &BufferedRecord, reserved: usize) -> BufferedRecord {
+        let max_chars = match (self.max_record_chars, self.max_chars) {
+            (Some(record_chars), Some(chars)) => record_chars.min(chars.saturating_sub(reserved)),
+            (Some(chars), None) => chars,
+            (None, Some(chars)) => chars.saturating_sub(reserved),
+            (None, None) => return record.clone(),
+        }
+        .max(1);
+        let mut record = record.clone();
+        if let Some((index, _)) = record.args.char_indices().nth(max_chars) {
+            let index = record.args[..index]
+                .char_indices()
+                .last()
+                .map_or(0, |(i, _)| i);
+            record.args.truncate(index);
+            record.args.push('\u{2026}');
+        }
+        record
+    }
+
+    /// Select records that fit in the limits,
+    /// the highest severity first, and then the most recent.
+    /// The `reserved` characters are excluded from [`ContentLimits::max_chars`].
+    /// Returns the selected and the omitted records,
+    /// both in the original order.
+    fn select(
+        &self,
+        records: Vec<BufferedRecord>,
+        reserved: usize,
+    ) -> (Vec<BufferedRecord>, Vec<BufferedRecord>) {
+        let max_chars = self
+            .max_chars
+            .map_or(usize::MAX, |chars| chars.saturating_sub(reserved));
+        if self.fits(&records, max_chars) {
+            return (records, Vec::new());
+        }
+        let max_records = self.max_records.unwrap_or(usize::MAX).max(1);
+        let mut order: Vec<usize> = (0..records.len()).collect();
+        order.sort_by_key(|&i| (records[i].level, usize::MAX - i));
+        let mut is_selected = vec![false; records.len()];
+        let mut count = 0;
+        let mut chars = 0;
+        for i in order {
+            if count >= max_records {
+                break;
+            }
+            let record_chars = records[i].args.chars().count() + 1;
+            if count > 0 && chars + record_chars > max_chars {
+                continue;
+            }
+            is_selected[i] = true;
+            count += 1;
+            chars += record_chars;
+        }
+        let (selected, omitted): (Vec<_>, Vec<_>) = records
+            .into_iter()
+            .zip(is_selected)
+            .partition(|(_, is_selected)| *is_selected);
+        (
+            selected.into_iter().map(|(r, _)| r).collect(),
+            omitted.into_iter().map(|(r, _)| r).collect(),
+        )
+    }
+
+    fn fits(&self, records: &[BufferedRecord], max_chars: usize) -> bool {
+        if self.max_records.is_some_and(|max| records.len() > max) {
+            return false;
+        }
+        let chars: usize = records.iter().map(|r| r.args.chars().count() + 1).sum();
+        chars <= max_chars
+    }
+
+    fn footer(omitted: &[BufferedRecord]) -> BufferedRecord {
+        let count: usize = omitted.iter().map(|r| r.count).sum();
+        let count_of = |level| {
+            omitted
+                .iter()
+                .filter(|r| r.level == level)
+                .map(|r| r.count)
+                .sum::<usize>()
+        };
+        let plural = |count: usize, noun: &str| match count {
+            1 => format!("1 {noun}"),
+            count => format!("{count} {noun}s"),
+        };
+        let details: Vec<String> = [
+            (count_of(log::Level::Error), "error"),
+            (count_of(log::Level::Warn), "warning"),
+        ]
+        .into_iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, noun)| plural(count, noun))
+        .collect();
+        let mut args = format!("\u{2026}and {count} more");
+        if !details.is_empty() {
+            args.push_str(&format!(" ({})", details.join(", ")));
+        }
+        let level = omitted
+            .iter()
+            .map(|r| r.level)
+            .min()
+            .unwrap_or(log::Level::Info);
+        BufferedRecord::new_summary(level, &args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records(levels_and_args: &[(log::Level, &str)]) -> Vec<BufferedRecord> {
+        levels_and_args
+            .iter()
+            .map(|(level, args)| BufferedRecord::new(*level, "", args))
+            .collect()
+    }
+
+    fn args(notifications: &[Vec<BufferedRecord>]) -> Vec<Vec<&str>> {
+        notifications
+            .iter()
+            .map(|records| records.iter().map(|r| r.args()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn max_records() {
+        let limits = ContentLimits {
+            max_records: Some(2),
+            ..Default::default()
+        };
+        let records = records(&[
+            (log::Level::Info, "1"),
+            (log::Level::Error, "2"),
+            (log::Level::Info, "3"),
+            (log::Level::Info, "4"),
+            (log::Level::Error, "5"),
+        ]);
+        assert_eq!(
+            args(&limits.apply(&records)),
+            [vec!["2", "5", "\u{2026}and 3 more"]]
+        );
+
+        let limits = ContentLimits {
+            max_records: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(
+            args(&limits.apply(&records)),
+            [vec!["2", "4", "5", "\u{2026}and 2 more"]]
+        );
+    }
+
+    #[test]
+    fn footer() {
+        let limits = ContentLimits {
+            max_records: Some(1),
+            ..Default::default()
+        };
+        let records = records(&[
+            (log::Level::Error, "1"),
+            (log::Level::Warn, "2"),
+            (log::Level::Error, "3"),
+            (log::Level::Info, "4"),
+        ]);
+        assert_eq!(
+            args(&limits.apply(&records)),
+            [vec!["3", "\u{2026}and 3 more (1 error, 1 warning)"]]
+        );
+    }
+
+    #[test]
+    fn max_chars() {
+        let limits = ContentLimits {
+            max_record_chars: Some(4),
+            ..Default::default()
+        };
+        let records = records(&[(log::Level::Info, "12345"), (log::Level::Info, "1234")]);
+        assert_eq!(args(&limits.apply(&records)), [vec!["123\u{2026}", "1234"]]);
+
+        let limits = ContentLimits {
+            max_chars: Some(ContentLimits::FOOTER_CHARS + 10),
+            ..Default::default()
+        };
+        let records: Vec<_> = (1..=7)
+            .map(|i| BufferedRecord::new(log::Level::Info, "", &i.to_string().repeat(9)))
+            .collect();
+        assert_eq!(
+            args(&limits.apply(&records)),
+            [vec!["777777777", "\u{2026}and 6 more"]]
+        );
+    }
+
+    #[test]
+    fn fit_without_footer() {
+        // Records that fit don't need the room for the footer.
+        let limits = ContentLimits {
+            max_record_chars: Some(100),
+            max_chars: Some(20),
+            ..Default::default()
+        };
+        let records = records(&[
+            (log::Level::Info, "123456789"),
+            (log::Level::Info, "abcdefghi"),
+        ]);
+        assert_eq!(
+            args(&limits.apply(&records)),
+            [vec!["123456789", "abcdefghi"]]
+        );
+
+        let limits = ContentLimits {
+            max_chars: Some(10),
+            ..Default::default()
+        };
+        let long = BufferedRecord::new(log::Level::Info, "", "1234567890123");
+        assert_eq!(args(&limits.apply(&[long])), [vec!["123456789\u{2026}"]]);
+    }
+
+    #[test]
+    fn split() {
+        let limits = ContentLimits {
+            max_records: Some(2),
+            is_split: true,
+            ..Default::default()
+        };
+        let records = records(&[
+            (log::Level::Info, "1"),
+            (log::Level::Error, "2"),
+            (log::Level::Info, "3"),
+        ]);
+        assert_eq!(args(&limits.apply(&records)), [vec!["2", "3"], vec!["1"]]);
+    }
+}
@@ -0,0 +1,106 @@
+//!
+//! Mocks for tests.
+//!
+
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    HistoryEntry, HistoryRemoval, Notification, NotifierBackend, Result, Schedule, ScheduledEntry,
+    events::NotificationEvents,
+};
+
+/// A [`NotifierBackend`] that records the operations
+/// instead of calling the Windows notifier APIs.
+///
+/// Together with the backend for other platforms,
+/// this makes the tests run off Windows.
+///
+/// Clones share the same state,
+/// so that tests can inspect the state after giving a clone to the logger.
+#[derive(Clone, Default)]
+pub(crate) struct MockNotifier {
+    state: Arc<Mutex<MockNotifierState>>,
+}
+
+#[derive(Default)]
+struct MockNotifierState {
+    shown: Vec<NotificationEvents>,
+    scheduled: Vec<Schedule>,
+    history: Vec<HistoryEntry>,
+    removals: Vec<HistoryRemoval>,
+}
+
+impl MockNotifier {
+    /// The number of notifications shown.
+    pub fn shown(&self) -> usize {
+        self.state.lock().unwrap().shown.len()
+    }
+
+    /// The events of the `index`-th shown notification,
+    /// to simulate the events from the system.
+    pub fn events(&self, index: usize) -> NotificationEvents {
+        self.state.lock().unwrap().shown[index].clone()
+    }
+
+    /// The pending schedules.
+    pub fn schedules(&self) -> Vec<Schedule> {
+        self.state.lock().unwrap().scheduled.clone()
+    }
+
+    /// The removals requested by [`NotifierBackend::remove_history()`].
+    pub fn removals(&self) -> Vec<HistoryRemoval> {
+        self.state.lock().unwrap().removals.clone()
+    }
+
+    /// Add a notification to the history as if it were shown.
+    pub fn add_history(&self, tag: &str, group: &str) {
+        self.state.lock().unwrap().history.push(HistoryEntry {
+            tag: tag.into(),
+            group: group.into(),
+        });
+    }
+}
+
+impl NotifierBackend for MockNotifier {
+    fn show(&self, _notification: &Notification, events: &NotificationEvents) -> Result<()> {
+        self.state.lock().unwrap().shown.push(events.clone());
+        Ok(())
+    }
+
+    fn add_to_schedule(&self, _notification: &Notification, schedule: &Schedule) -> Result<()> {
+        self.state.lock().unwrap().scheduled.push(schedule.clone());
+        Ok(())
+    }
+
+    fn scheduled(&self) -> Result<Vec<ScheduledEntry>> {
+        let state = self.state.lock().unwrap();
+        let entries = state.scheduled.iter().map(|schedule| ScheduledEntry {
+            tag: schedule.tag.clone(),
+            group: schedule.group.clone(),
+            delivery_time: schedule.delivery_time,
+        });
+        Ok(entries.collect())
+    }
+
+    fn remove_from_schedule(&self, tag: &str) -> Result<usize> {
+        let scheduled = &mut self.state.lock().unwrap().scheduled;
+        let len = scheduled.len();
+        scheduled.retain(|entry| entry.tag != tag);
+        Ok(len - scheduled.len())
+    }
+
+    fn history(&self) -> Result<Vec<HistoryEntry>> {
+        Ok(self.state.lock().unwrap().history.clone())
+    }
+
+    fn remove_history(&self, removal: &HistoryRemoval) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.history.retain(|entry| match removal {
+            HistoryRemoval::Tag { tag, group } => entry.tag != *tag || entry.group != *group,
+            HistoryRemoval::Group(group) => entry.group != *group,
+            HistoryRemoval::All => false,
+        });
+        state.removals.push(removal.clone());
+        Ok(())
+    }
+}
@@ -1,27 +1,62 @@
-use crate::Result;
+use std::time::{Duration, SystemTime};
+
+use crate::{Error, Result, Schedule, ScheduledEntry, events::NotificationEvents};
+
+#[cfg(all(windows, feature = "winrt-toast"))]
+use crate::winrt::{ToastExt, ToastManagerExt};
 
 #[cfg(doc)]
-use crate::ToastLoggerBuilder;
+use crate::{ToastLogger, ToastLoggerBuilder};
 
 /// A struct to own copies of parts of [`log::Record`] for buffering.
 ///
 /// The [`log::Record`] has lifetime for the lower overhead,
 /// and it's not suitable to buffer them.
 /// This struct captures the data needed for longer lifetime.
-#[derive(Debug, PartialEq, Eq)]
+///
+/// Two records are equal when their levels, targets, and messages are equal,
+/// regardless of their counts and timestamps.
+/// Please see [`ToastLoggerBuilder::dedup()`].
+#[derive(Clone, Debug)]
 pub struct BufferedRecord {
     pub level: log::Level,
+    pub target: String,
     pub args: String,
+    pub(crate) count: usize,
+    pub(crate) first_time: SystemTime,
+    pub(crate) last_time: SystemTime,
+    /// Whether this summarizes other records, such as dropped ones,
+    /// rather than being logged.
+    pub(crate) is_summary: bool,
 }
 
 impl BufferedRecord {
-    pub fn new_with_formatted_args(record: &log::Record, args: &str) -> Self {
+    pub fn new(level: log::Level, target: &str, args: &str) -> Self {
+        let now = SystemTime::now();
+        Self {
+            level,
+            target: target.into(),
+            args: args.into(),
+            count: 1,
+            first_time: now,
+            last_time: now,
+            is_summary: false,
+        }
+    }
+
+    /// A record that summarizes other records,
+    /// such as "…and 3 more messages dropped".
+    pub(crate) fn new_summary(level: log::Level, args: &str) -> Self {
         Self {
-            level: record.level(),
-            args: args.to_string(),
+            is_summary: true,
+            ..Self::new(level, "", args)
         }
     }
 
+    pub fn new_with_formatted_args(record: &log::Record, args: &str) -> Self {
+        Self::new(record.level(), record.target(), args)
+    }
+
     pub fn level(&self) -> log::Level {
         self.level
     }
@@ -29,6 +64,72 @@ impl BufferedRecord {
     pub fn args(&self) -> &str {
         &self.args
     }
+
+    /// The number of times this record was logged.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// The time this record was logged first.
+    pub fn first_time(&self) -> SystemTime {
+        self.first_time
+    }
+
+    /// The time this record was logged last.
+    pub fn last_time(&self) -> SystemTime {
+        self.last_time
+    }
+
+    /// Merge the counts and the timestamps of an equal `record`.
+    pub(crate) fn merge(&mut self, record: &BufferedRecord) {
+        debug_assert_eq!(self, record);
+        self.count += record.count;
+        self.first_time = self.first_time.min(record.first_time);
+        self.last_time = self.last_time.max(record.last_time);
+    }
+}
+
+impl PartialEq for BufferedRecord {
+    fn eq(&self, other: &Self) -> bool {
+        self.level == other.level && self.target == other.target && self.args == other.args
+    }
+}
+
+impl Eq for BufferedRecord {}
+
+/// Whether a notification is mirrored to other devices.
+/// Please see [`NotificationMirroring`][mirroring].
+///
+/// [mirroring]: https://learn.microsoft.com/uwp/api/windows.ui.notifications.notificationmirroring
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NotificationMirroring {
+    /// Allow the notification to be mirrored.
+    #[default]
+    Allowed,
+    /// Don't mirror the notification.
+    Disabled,
+}
+
+/// A notification in the Action Center.
+///
+/// Please see [`ToastLogger::history()`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HistoryEntry {
+    pub tag: String,
+    pub group: String,
+}
+
+/// The notifications to remove from the Action Center.
+///
+/// Please see [`ToastLogger::remove_shown()`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum HistoryRemoval {
+    /// The notification with the tag and the group.
+    Tag { tag: String, group: String },
+    /// The notifications in the group.
+    Group(String),
+    /// All notifications of the application ID.
+    All,
 }
 
 /// Abstracted notification.
@@ -46,12 +147,15 @@ impl BufferedRecord {
 ///   enabled by the feature `winrt-toast`.
 ///   This crate provides additional features and controls.
 ///
+/// On other platforms than Windows, notifications can be created,
+/// but showing them returns [`Error::UnsupportedPlatform`].
+///
 /// [`windows` crate]: https://crates.io/crates/windows
 /// [`winrt-toast` crate]: https://docs.rs/winrt-toast/latest/winrt_toast/
 pub struct Notification {
-    #[cfg(not(feature = "winrt-toast"))]
+    #[cfg(not(all(windows, feature = "winrt-toast")))]
     inner: crate::win::NotificationImpl,
-    #[cfg(feature = "winrt-toast")]
+    #[cfg(all(windows, feature = "winrt-toast"))]
     inner: winrt_toast::Toast,
 }
 
@@ -59,9 +163,9 @@ impl Notification {
     /// Construct from a string.
     pub fn new_with_text(text: &str) -> Result<Self> {
         Ok(Self {
-            #[cfg(not(feature = "winrt-toast"))]
+            #[cfg(not(all(windows, feature = "winrt-toast")))]
             inner: crate::win::NotificationImpl::new_with_text(text)?,
-            #[cfg(feature = "winrt-toast")]
+            #[cfg(all(windows, feature = "winrt-toast"))]
             inner: {
                 let mut toast = winrt_toast::Toast::new();
                 toast.text1(text);
@@ -71,31 +175,156 @@ impl Notification {
     }
 
     /// Construct from a list of [`BufferedRecord`].
+    ///
+    /// Records logged more than once have their counts,
+    /// and how long ago they were logged first and last,
+    /// such as "(×12, first 5m ago, last 10s ago)".
     pub fn new_with_records(records: &[BufferedRecord]) -> Result<Self> {
-        let text = records
+        Self::new_with_text(&Self::records_text(records, SystemTime::now()))
+    }
+
+    /// The text of [`Notification::new_with_records()`] shown at `now`.
+    fn records_text(records: &[BufferedRecord], now: SystemTime) -> String {
+        let ago = |time: SystemTime| {
+            let seconds = now.duration_since(time).unwrap_or_default().as_secs();
+            match seconds {
+                0..60 => format!("{seconds}s"),
+                60..3600 => format!("{}m", seconds / 60),
+                3600..86400 => format!("{}h", seconds / 3600),
+                _ => format!("{}d", seconds / 86400),
+            }
+        };
+        records
             .iter()
-            .map(|r| r.args.as_str())
+            .map(|r| match r.count {
+                0 | 1 => r.args.clone(),
+                count => format!(
+                    "{} (\u{d7}{count}, first {} ago, last {} ago)",
+                    r.args,
+                    ago(r.first_time),
+                    ago(r.last_time)
+                ),
+            })
             .collect::<Vec<_>>()
-            .join("\n");
-        Self::new_with_text(&text)
+            .join("\n")
     }
 
-    /// Set the expirations of this notification.
+    /// Set the expirations of this notification
+    /// to the `duration` from the current time.
     /// Please see [`ToastNotification.ExpirationTime`].
     ///
+    /// Returns [`Error::ExpirationOutOfRange`] if the time can't be represented.
+    ///
     /// [`ToastNotification.ExpirationTime`]: https://learn.microsoft.com/uwp/api/windows.ui.notifications.toastnotification.expirationtime
-    pub fn expires_in(&mut self, duration: std::time::Duration) -> Result<()> {
-        #[cfg(not(feature = "winrt-toast"))]
-        self.inner.expires_in(duration)?;
-        #[cfg(feature = "winrt-toast")]
-        self.inner.expires_in(duration);
-        Ok(())
+    pub fn expires_in(&mut self, duration: Duration) -> Result<()> {
+        #[cfg(not(all(windows, feature = "winrt-toast")))]
+        {
+            let time = SystemTime::now()
+                .checked_add(duration)
+                .ok_or(Error::ExpirationOutOfRange)?;
+            // Not `expires_at()`, so that a zero `duration` is allowed.
+            self.inner.expires_at(time)
+        }
+        #[cfg(all(windows, feature = "winrt-toast"))]
+        {
+            self.inner.expires_in(duration);
+            Ok(())
+        }
+    }
+
+    /// Set the expirations of this notification to the `time`.
+    /// Please see [`ToastNotification.ExpirationTime`].
+    ///
+    /// Returns [`Error::ExpirationInPast`] if the `time` is not in the future,
+    /// or [`Error::ExpirationOutOfRange`] if the `time` can't be represented.
+    ///
+    /// [`ToastNotification.ExpirationTime`]: https://learn.microsoft.com/uwp/api/windows.ui.notifications.toastnotification.expirationtime
+    pub fn expires_at(&mut self, time: SystemTime) -> Result<()> {
+        if time <= SystemTime::now() {
+            return Err(Error::ExpirationInPast);
+        }
+        self.inner.expires_at(time)
+    }
+
+    /// Set whether this notification expires when the system reboots.
+    /// Please see [`ToastNotification.ExpiresOnReboot`].
+    ///
+    /// [`ToastNotification.ExpiresOnReboot`]: https://learn.microsoft.com/uwp/api/windows.ui.notifications.toastnotification.expiresonreboot
+    pub fn expires_on_reboot(&mut self, value: bool) -> Result<()> {
+        self.inner.expires_on_reboot(value)
+    }
+
+    /// Set the tag of this notification.
+    /// Please see [`ToastNotification.Tag`].
+    ///
+    /// [`ToastNotification.Tag`]: https://learn.microsoft.com/uwp/api/windows.ui.notifications.toastnotification.tag
+    pub fn tag(&mut self, value: &str) -> Result<()> {
+        #[cfg(not(all(windows, feature = "winrt-toast")))]
+        {
+            self.inner.tag(value)
+        }
+        #[cfg(all(windows, feature = "winrt-toast"))]
+        {
+            self.inner.tag(value);
+            Ok(())
+        }
+    }
+
+    /// Set the group of this notification.
+    /// Please see [`ToastNotification.Group`].
+    ///
+    /// [`ToastNotification.Group`]: https://learn.microsoft.com/uwp/api/windows.ui.notifications.toastnotification.group
+    pub fn group(&mut self, value: &str) -> Result<()> {
+        #[cfg(not(all(windows, feature = "winrt-toast")))]
+        {
+            self.inner.group(value)
+        }
+        #[cfg(all(windows, feature = "winrt-toast"))]
+        {
+            self.inner.group(value);
+            Ok(())
+        }
+    }
+
+    /// Set whether to suppress the popup UI of this notification.
+    /// When this is `true`,
+    /// the notification is delivered directly to the Action Center.
+    /// Please see [`ToastNotification.SuppressPopup`].
+    ///
+    /// [`ToastNotification.SuppressPopup`]: https://learn.microsoft.com/uwp/api/windows.ui.notifications.toastnotification.suppresspopup
+    pub fn suppress_popup(&mut self, value: bool) -> Result<()> {
+        self.inner.suppress_popup(value)
+    }
+
+    /// Set whether this notification is mirrored to other devices.
+    /// Please see [`ToastNotification.NotificationMirroring`].
+    ///
+    /// [`ToastNotification.NotificationMirroring`]: https://learn.microsoft.com/uwp/api/windows.ui.notifications.toastnotification.notificationmirroring
+    pub fn notification_mirroring(&mut self, value: NotificationMirroring) -> Result<()> {
+        self.inner.notification_mirroring(value)
+    }
+
+    /// Set the ID to correlate this notification
+    /// with the notifications on other devices.
+    /// Please see [`ToastNotification.RemoteId`].
+    ///
+    /// [`ToastNotification.RemoteId`]: https://learn.microsoft.com/uwp/api/windows.ui.notifications.toastnotification.remoteid
+    pub fn remote_id(&mut self, value: &str) -> Result<()> {
+        #[cfg(not(all(windows, feature = "winrt-toast")))]
+        {
+            self.inner.remote_id(value)
+        }
+        #[cfg(all(windows, feature = "winrt-toast"))]
+        {
+            self.inner.remote_id(value);
+            Ok(())
+        }
     }
 
     /// The inner [`winrt_toast::Toast`].
     ///
     /// Available only when the "`winrt-toast`" feature is enabled.
-    #[cfg(feature = "winrt-toast")]
+    #[cfg(all(windows, feature = "winrt-toast"))]
     // error[E0658]: `#[doc(cfg)]` is experimental
     // https://github.com/rust-lang/rust/issues/43781
     // #[cfg_attr(docsrs, doc(cfg(feature = "winrt-toast")))]
@@ -106,7 +335,7 @@ impl Notification {
     /// The mutable inner [`winrt_toast::Toast`].
     ///
     /// Available only when the "`winrt-toast`" feature is enabled.
-    #[cfg(feature = "winrt-toast")]
+    #[cfg(all(windows, feature = "winrt-toast"))]
     // error[E0658]: `#[doc(cfg)]` is experimental
     // https://github.com/rust-lang/rust/issues/43781
     // #[cfg_attr(docsrs, doc(cfg(feature = "winrt-toast")))]
@@ -115,26 +344,136 @@ impl Notification {
     }
 }
 
+/// Operations of the underlying notifier.
+///
+/// This trait abstracts the [`Notifier`] so that tests can replace it.
+pub(crate) trait NotifierBackend: Send + Sync {
+    fn show(&self, notification: &Notification, events: &NotificationEvents) -> Result<()>;
+    fn add_to_schedule(&self, notification: &Notification, schedule: &Schedule) -> Result<()>;
+    fn scheduled(&self) -> Result<Vec<ScheduledEntry>>;
+    fn remove_from_schedule(&self, tag: &str) -> Result<usize>;
+    fn history(&self) -> Result<Vec<HistoryEntry>>;
+    fn remove_history(&self, removal: &HistoryRemoval) -> Result<()>;
+}
+
 /// Abstracted notifier for the `Notification`.
 pub(crate) struct Notifier {
-    #[cfg(not(feature = "winrt-toast"))]
+    #[cfg(not(all(windows, feature = "winrt-toast")))]
     inner: crate::win::NotifierImpl,
-    #[cfg(feature = "winrt-toast")]
+    #[cfg(all(windows, feature = "winrt-toast"))]
     inner: winrt_toast::ToastManager,
 }
 
 impl Notifier {
     pub fn new_with_application_id(application_id: &str) -> Result<Self> {
         Ok(Self {
-            #[cfg(not(feature = "winrt-toast"))]
+            #[cfg(not(all(windows, feature = "winrt-toast")))]
             inner: crate::win::NotifierImpl::new_with_application_id(application_id)?,
-            #[cfg(feature = "winrt-toast")]
+            #[cfg(all(windows, feature = "winrt-toast"))]
             inner: winrt_toast::ToastManager::new(application_id),
         })
     }
+}
+
+impl NotifierBackend for Notifier {
+    fn show(&self, notification: &Notification, events: &NotificationEvents) -> Result<()> {
+        self.inner.show_with_events(&notification.inner, events)
+    }
+
+    fn add_to_schedule(&self, notification: &Notification, schedule: &Schedule) -> Result<()> {
+        self.inner.add_to_schedule(&notification.inner, schedule)
+    }
+
+    fn scheduled(&self) -> Result<Vec<ScheduledEntry>> {
+        self.inner.scheduled()
+    }
+
+    fn remove_from_schedule(&self, tag: &str) -> Result<usize> {
+        self.inner.remove_from_schedule(tag)
+    }
+
+    fn history(&self) -> Result<Vec<HistoryEntry>> {
+        self.inner.history()
+    }
+
+    fn remove_history(&self, removal: &HistoryRemoval) -> Result<()> {
+        self.inner.remove_history(removal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_text() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(10 * 86400);
+        let mut repeated = BufferedRecord::new(log::Level::Error, "", "repeated");
+        repeated.count = 12;
+        repeated.first_time = now - Duration::from_secs(5 * 60 + 30);
+        repeated.last_time = now - Duration::from_secs(10);
+        let mut old = BufferedRecord::new(log::Level::Info, "", "old");
+        old.count = 2;
+        old.first_time = now - Duration::from_secs(2 * 86400);
+        old.last_time = now - Duration::from_secs(3 * 3600);
+        let records = [
+            BufferedRecord::new(log::Level::Error, "", "once"),
+            repeated,
+            old,
+        ];
+        assert_eq!(
+            Notification::records_text(&records, now),
+            "once\n\
+             repeated (\u{d7}12, first 5m ago, last 10s ago)\n\
+             old (\u{d7}2, first 2d ago, last 3h ago)"
+        );
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn properties() -> Result<()> {
+        let mut notification = Notification::new_with_text("test")?;
+        notification.expires_in(Duration::ZERO)?;
+        notification.expires_on_reboot(true)?;
+        notification.tag("tag")?;
+        notification.group("group")?;
+        notification.suppress_popup(true)?;
+        notification.notification_mirroring(NotificationMirroring::Disabled)?;
+        notification.remote_id("remote")?;
+        let inner = &notification.inner;
+        assert_eq!(inner.text, "test");
+        assert!(inner.expiration_time.is_some());
+        assert!(inner.is_expires_on_reboot);
+        assert_eq!(inner.tag, "tag");
+        assert_eq!(inner.group, "group");
+        assert!(inner.is_suppress_popup);
+        assert_eq!(inner.mirroring, NotificationMirroring::Disabled);
+        assert_eq!(inner.remote_id, "remote");
+
+        assert!(matches!(
+            notification.expires_at(SystemTime::UNIX_EPOCH),
+            Err(Error::ExpirationInPast)
+        ));
+        Ok(())
+    }
 
-    pub fn show(&self, notification: &Notification) -> Result<()> {
-        self.inner.show(&notification.inner)?;
+    #[cfg(not(windows))]
+    #[test]
+    fn notifier() -> Result<()> {
+        let notifier = Notifier::new_with_application_id("test")?;
+        let notification = Notification::new_with_text("test")?;
+        assert!(matches!(
+            notifier.show(&notification, &NotificationEvents::default()),
+            Err(Error::UnsupportedPlatform)
+        ));
+        assert!(matches!(
+            notifier.history(),
+            Err(Error::UnsupportedPlatform)
+        ));
+        assert!(matches!(
+            notifier.remove_history(&HistoryRemoval::All),
+            Err(Error::UnsupportedPlatform)
+        ));
         Ok(())
     }
 }
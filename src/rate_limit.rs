@@ -0,0 +1,269 @@
+use std::{
+    collections::HashMap,
+    mem,
+    time::{Duration, Instant},
+};
+
+use crate::BufferedRecord;
+
+#[cfg(doc)]
+use crate::ToastLoggerBuilder;
+
+/// A token bucket that allows `capacity` notifications in bursts,
+/// and refills them evenly over the `period`.
+#[derive(Clone, Debug)]
+struct TokenBucket {
+    capacity: u32,
+    interval: Duration,
+    tokens: u32,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    fn new(capacity: u32, period: Duration, now: Instant) -> Self {
+        Self {
+            capacity,
+            interval: period.checked_div(capacity).unwrap_or_default(),
+            tokens: capacity,
+            refilled_at: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        if self.tokens >= self.capacity || self.interval.is_zero() {
+            self.tokens = self.capacity;
+            self.refilled_at = now;
+            return;
+        }
+        let elapsed = now.saturating_duration_since(self.refilled_at);
+        let count = (elapsed.as_nanos() / self.interval.as_nanos()).min(u32::MAX as u128) as u32;
+        if count == 0 {
+            return;
+        }
+        self.tokens = self.tokens.saturating_add(count).min(self.capacity);
+        self.refilled_at = if self.tokens >= self.capacity {
+            now
+        } else {
+            self.refilled_at + self.interval * count
+        };
+    }
+
+    fn has_token(&self) -> bool {
+        self.tokens > 0
+    }
+
+    fn take(&mut self) {
+        self.tokens = self.tokens.saturating_sub(1);
+    }
+}
+
+/// Limits the rate of notifications, globally and per target.
+///
+/// Please see [`ToastLoggerBuilder::rate_limit()`].
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    global: Option<TokenBucket>,
+    per_target: Option<(u32, Duration)>,
+    targets: HashMap<String, TokenBucket>,
+    suppressed: usize,
+    suppressed_level: Option<log::Level>,
+    /// The suppressed summaries, such as dropped records,
+    /// to show with the next notification.
+    suppressed_summaries: Vec<BufferedRecord>,
+    /// The total number since created, for [`crate::Stats`].
+    total_suppressed: u64,
+}
+
+impl RateLimiter {
+    pub fn new(global: Option<(u32, Duration)>, per_target: Option<(u32, Duration)>) -> Self {
+        let now = Instant::now();
+        Self {
+            global: global.map(|(capacity, period)| TokenBucket::new(capacity, period, now)),
+            per_target,
+            targets: HashMap::new(),
+            suppressed: 0,
+            suppressed_level: None,
+            suppressed_summaries: Vec::new(),
+            total_suppressed: 0,
+        }
+    }
+
+    /// Take tokens to show the `records`, and returns the records to show.
+    ///
+    /// The records of the targets without tokens are suppressed and counted.
+    /// If there are suppressed records, a summary record is appended
+    /// to the next notification.
+    /// Summaries in the `records` are not limited per target,
+    /// and suppressed summaries are appended to the next notification as they are.
+    /// Returns an empty `Vec` if nothing should be shown.
+    pub fn admit(&mut self, records: &[BufferedRecord]) -> Vec<BufferedRecord> {
+        self.admit_at(records, Instant::now())
+    }
+
+    fn admit_at(&mut self, records: &[BufferedRecord], now: Instant) -> Vec<BufferedRecord> {
+        let mut admitted = Vec::with_capacity(records.len() + 1);
+        for record in records {
+            if record.is_summary || self.target_has_token(&record.target, now) {
+                admitted.push(record.clone());
+            } else {
+                self.suppress(record);
+            }
+        }
+
+        let global_has_token = match &mut self.global {
+            Some(global) => {
+                global.refill(now);
+                global.has_token()
+            }
+            None => true,
+        };
+        if admitted.is_empty() || !global_has_token {
+            for record in &admitted {
+                self.suppress(record);
+            }
+            return Vec::new();
+        }
+
+        if let Some(global) = &mut self.global {
+            global.take();
+        }
+        if self.per_target.is_some() {
+            let mut targets: Vec<&str> = admitted
+                .iter()
+                .filter(|record| !record.is_summary)
+                .map(|record| record.target.as_str())
+                .collect();
+            targets.sort_unstable();
+            targets.dedup();
+            for target in targets {
+                if let Some(bucket) = self.targets.get_mut(target) {
+                    bucket.take();
+                }
+            }
+        }
+
+        admitted.append(&mut self.suppressed_summaries);
+        if let Some(level) = self.suppressed_level.take() {
+            let count = mem::take(&mut self.suppressed);
+            let noun = if count == 1 { "message" } else { "messages" };
+            let args = format!("\u{2026}and {count} more {noun} suppressed");
+            admitted.push(BufferedRecord::new_summary(level, &args));
+        }
+        admitted
+    }
+
+    /// The total number of suppressed records.
+    pub fn total_suppressed(&self) -> u64 {
+        self.total_suppressed
+    }
+
+    fn target_has_token(&mut self, target: &str, now: Instant) -> bool {
+        let Some((capacity, period)) = self.per_target else {
+            return true;
+        };
+        let bucket = self
+            .targets
+            .entry(target.into())
+            .or_insert_with(|| TokenBucket::new(capacity, period, now));
+        bucket.refill(now);
+        bucket.has_token()
+    }
+
+    fn suppress(&mut self, record: &BufferedRecord) {
+        if record.is_summary {
+            self.suppressed_summaries.push(record.clone());
+            return;
+        }
+        // Deduplicated records count all their occurrences.
+        self.suppressed += record.count;
+        self.total_suppressed += record.count as u64;
+        self.suppressed_level = Some(
+            self.suppressed_level
+                .map_or(record.level, |level| level.min(record.level)),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(target: &str, args: &str) -> BufferedRecord {
+        BufferedRecord::new(log::Level::Error, target, args)
+    }
+
+    #[test]
+    fn token_bucket() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new(2, Duration::from_secs(10), now);
+        bucket.take();
+        bucket.take();
+        assert!(!bucket.has_token());
+        bucket.refill(now + Duration::from_secs(4));
+        assert!(!bucket.has_token());
+        bucket.refill(now + Duration::from_secs(5));
+        assert_eq!(bucket.tokens, 1);
+        bucket.refill(now + Duration::from_secs(60));
+        assert_eq!(bucket.tokens, 2);
+    }
+
+    #[test]
+    fn global() {
+        let now = Instant::now();
+        let mut limiter = RateLimiter::new(Some((1, Duration::from_secs(10))), None);
+        assert_eq!(limiter.admit_at(&[record("a", "1")], now).len(), 1);
+        assert!(limiter.admit_at(&[record("a", "2")], now).is_empty());
+        assert!(limiter.admit_at(&[record("a", "3")], now).is_empty());
+        assert_eq!(
+            limiter.admit_at(&[record("a", "4")], now + Duration::from_secs(10)),
+            [
+                record("a", "4"),
+                record("", "\u{2026}and 2 more messages suppressed"),
+            ]
+        );
+        assert_eq!(limiter.total_suppressed(), 2);
+    }
+
+    #[test]
+    fn per_target() {
+        let now = Instant::now();
+        let mut limiter = RateLimiter::new(None, Some((1, Duration::from_secs(10))));
+        assert_eq!(limiter.admit_at(&[record("a", "1")], now).len(), 1);
+        assert_eq!(
+            limiter.admit_at(&[record("a", "2"), record("b", "3")], now),
+            [
+                record("b", "3"),
+                record("", "\u{2026}and 1 more message suppressed"),
+            ]
+        );
+        assert!(limiter.admit_at(&[record("b", "4")], now).is_empty());
+    }
+
+    #[test]
+    fn deduplicated_and_summaries() {
+        let now = Instant::now();
+        let mut limiter = RateLimiter::new(Some((1, Duration::from_secs(10))), None);
+        assert_eq!(limiter.admit_at(&[record("a", "1")], now).len(), 1);
+        let mut deduplicated = record("a", "2");
+        deduplicated.count = 3;
+        let dropped =
+            BufferedRecord::new_summary(log::Level::Warn, "\u{2026}and 2 more messages dropped");
+        assert!(
+            limiter
+                .admit_at(&[deduplicated, dropped.clone()], now)
+                .is_empty()
+        );
+        assert_eq!(limiter.total_suppressed(), 3);
+
+        let admitted = limiter.admit_at(&[record("a", "3")], now + Duration::from_secs(10));
+        assert_eq!(
+            admitted,
+            [
+                record("a", "3"),
+                dropped,
+                record("", "\u{2026}and 3 more messages suppressed"),
+            ]
+        );
+        assert_eq!(admitted[2].level, log::Level::Error);
+    }
+}
@@ -0,0 +1,356 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    panic::{self, AssertUnwindSafe},
+    sync::{Arc, Condvar, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{BufferedRecord, Error, Result, sync::IgnorePoison};
+
+#[cfg(doc)]
+use crate::ToastLoggerBuilder;
+
+/// How to retry notifications that failed to show
+/// by [transient errors].
+///
+/// Please see [`ToastLoggerBuilder::retry()`].
+///
+/// # Examples
+/// ```
+/// # use std::time::Duration;
+/// # use toast_logger_win::RetryPolicy;
+/// let mut policy = RetryPolicy::new(5);
+/// policy
+///     .backoff(Duration::from_millis(500), Duration::from_secs(60))
+///     .jitter(false);
+/// ```
+///
+/// [transient errors]: Error::is_transient()
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    is_jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    /// 3 attempts, with the backoff from 1 second up to 30 seconds.
+    fn default() -> Self {
+        Self::new(3)
+    }
+}
+
+impl RetryPolicy {
+    /// Construct with the maximum number of attempts,
+    /// including the first one.
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+            is_jitter: true,
+        }
+    }
+
+    /// Set the delay before the first retry,
+    /// and the maximum delay.
+    /// The delay doubles on each retry.
+    pub fn backoff(&mut self, initial: Duration, max: Duration) -> &mut Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max.max(initial);
+        self
+    }
+
+    /// Randomize each delay between the half and the full of it,
+    /// so that multiple processes don't retry at the same time.
+    /// The default is `true`.
+    pub fn jitter(&mut self, value: bool) -> &mut Self {
+        self.is_jitter = value;
+        self
+    }
+
+    /// The delay after the `attempt`-th attempt failed.
+    fn delay(&self, attempt: u32) -> Duration {
+        let delay = 2u32
+            .checked_pow(attempt.saturating_sub(1))
+            .and_then(|factor| self.initial_backoff.checked_mul(factor))
+            .map_or(self.max_backoff, |delay| delay.min(self.max_backoff));
+        if !self.is_jitter {
+            return delay;
+        }
+        let random = RandomState::new().build_hasher().finish();
+        let ratio = 0.5 + (random % 1000) as f64 / 2000.0;
+        delay.mul_f64(ratio)
+    }
+}
+
+/// Records waiting for the next attempt.
+struct Pending {
+    due: Instant,
+    attempt: u32,
+    records: Vec<BufferedRecord>,
+}
+
+#[derive(Default)]
+struct RetryState {
+    pending: Vec<Pending>,
+    /// Whether an attempt is running outside of the lock.
+    is_showing: bool,
+    is_stopped: bool,
+}
+
+/// A timer thread that retries showing records.
+///
+/// Please see [`ToastLoggerBuilder::retry()`].
+pub(crate) struct Retrier {
+    policy: RetryPolicy,
+    shared: Arc<(Mutex<RetryState>, Condvar)>,
+}
+
+impl Retrier {
+    /// Start the timer thread.
+    /// The `show` is called on the timer thread for each attempt.
+    /// The `give_up` is called with the last error
+    /// when the error is not transient, or no attempts are left.
+    /// The thread stops when the `Retrier` is dropped.
+    /// It continues when the callbacks panic.
+    ///
+    /// Returns [`Error::SpawnThread`] if the thread can't be spawned.
+    pub fn new<F, G>(policy: RetryPolicy, show: F, give_up: G) -> Result<Self>
+    where
+        F: Fn(&[BufferedRecord]) -> Result<()> + Send + 'static,
+        G: Fn(Error, Vec<BufferedRecord>) + Send + 'static,
+    {
+        let shared = Arc::new((Mutex::new(RetryState::default()), Condvar::new()));
+        let thread_shared = shared.clone();
+        let thread_policy = policy.clone();
+        thread::Builder::new()
+            .name("toast-logger-retry".into())
+            .spawn(move || Self::run(&thread_shared, &thread_policy, show, give_up))
+            .map_err(Error::SpawnThread)?;
+        Ok(Self { policy, shared })
+    }
+
+    /// Whether the `error` should be retried.
+    pub fn is_retryable(&self, error: &Error) -> bool {
+        self.policy.max_attempts > 1 && error.is_transient()
+    }
+
+    /// Retry showing the `records` after their first attempt failed.
+    pub fn retry(&self, records: Vec<BufferedRecord>) {
+        let (state, condvar) = &*self.shared;
+        let mut state = state.lock().ignore_poison();
+        Self::schedule(&mut state, &self.policy, 1, records);
+        condvar.notify_all();
+    }
+
+    /// Retry all pending records now,
+    /// and wait until they are shown or given up, up to the `timeout`.
+    /// Returns the records still pending after the `timeout`.
+    pub fn flush(&self, timeout: Duration) -> Vec<BufferedRecord> {
+        let (state, condvar) = &*self.shared;
+        let mut state = state.lock().ignore_poison();
+        let now = Instant::now();
+        for pending in &mut state.pending {
+            pending.due = now;
+        }
+        condvar.notify_all();
+        let mut state = condvar
+            .wait_timeout_while(state, timeout, |state| {
+                !state.pending.is_empty() || state.is_showing
+            })
+            .ignore_poison()
+            .0;
+        state
+            .pending
+            .drain(..)
+            .flat_map(|pending| pending.records)
+            .collect()
+    }
+
+    fn schedule(
+        state: &mut RetryState,
+        policy: &RetryPolicy,
+        failed_attempt: u32,
+        records: Vec<BufferedRecord>,
+    ) {
+        state.pending.push(Pending {
+            due: Instant::now() + policy.delay(failed_attempt),
+            attempt: failed_attempt + 1,
+            records,
+        });
+    }
+
+    fn run<F, G>(shared: &(Mutex<RetryState>, Condvar), policy: &RetryPolicy, show: F, give_up: G)
+    where
+        F: Fn(&[BufferedRecord]) -> Result<()>,
+        G: Fn(Error, Vec<BufferedRecord>),
+    {
+        let (state, condvar) = shared;
+        let mut state = state.lock().ignore_poison();
+        while !state.is_stopped {
+            let Some((index, due)) = state
+                .pending
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, pending)| pending.due)
+                .map(|(index, pending)| (index, pending.due))
+            else {
+                state = condvar.wait(state).ignore_poison();
+                continue;
+            };
+            let now = Instant::now();
+            if now < due {
+                state = condvar.wait_timeout(state, due - now).ignore_poison().0;
+                continue;
+            }
+            let pending = state.pending.swap_remove(index);
+            state.is_showing = true;
+            drop(state);
+            // The panics are reported by the panic hook,
+            // and the records are not retried.
+            let result = panic::catch_unwind(AssertUnwindSafe(|| show(&pending.records)));
+            state = shared.0.lock().ignore_poison();
+            if let Ok(Err(error)) = result {
+                if pending.attempt < policy.max_attempts && error.is_transient() {
+                    Self::schedule(&mut state, policy, pending.attempt, pending.records);
+                } else {
+                    drop(state);
+                    let _ = panic::catch_unwind(AssertUnwindSafe(|| {
+                        give_up(error, pending.records);
+                    }));
+                    state = shared.0.lock().ignore_poison();
+                }
+            }
+            state.is_showing = false;
+            // Wake up `flush()`.
+            condvar.notify_all();
+        }
+    }
+}
+
+impl Drop for Retrier {
+    fn drop(&mut self) {
+        let (state, condvar) = &*self.shared;
+        state.lock().ignore_poison().is_stopped = true;
+        condvar.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+
+    #[test]
+    fn delay() {
+        let mut policy = RetryPolicy::new(10);
+        policy
+            .backoff(Duration::from_secs(1), Duration::from_secs(5))
+            .jitter(false);
+        let delays: Vec<_> = (1..=5)
+            .map(|attempt| policy.delay(attempt).as_secs())
+            .collect();
+        assert_eq!(delays, [1, 2, 4, 5, 5]);
+        assert_eq!(policy.delay(100), Duration::from_secs(5));
+
+        policy.jitter(true);
+        for attempt in 1..=5 {
+            let delay = policy.delay(attempt);
+            let max = Duration::from_secs(1 << (attempt - 1)).min(Duration::from_secs(5));
+            assert!(delay >= max / 2 && delay <= max, "{delay:?}");
+        }
+    }
+
+    #[test]
+    fn give_up() -> Result<()> {
+        let mut policy = RetryPolicy::new(3);
+        policy.backoff(Duration::ZERO, Duration::ZERO);
+        let (sender, receiver) = mpsc::channel();
+        let attempts = Arc::new(Mutex::new(0));
+        let attempts_clone = attempts.clone();
+        let retrier = Retrier::new(
+            policy,
+            move |_| {
+                *attempts_clone.lock().unwrap() += 1;
+                Err(Error::NotInitialized)
+            },
+            move |error, records| {
+                let _ = sender.send((error, records));
+            },
+        )?;
+        assert!(!retrier.is_retryable(&Error::NotInitialized));
+        retrier.retry(vec![BufferedRecord::new(log::Level::Error, "", "1")]);
+        let (error, records) = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(matches!(error, Error::NotInitialized));
+        assert_eq!(records, [BufferedRecord::new(log::Level::Error, "", "1")]);
+        // Errors that are not transient are not retried.
+        assert_eq!(*attempts.lock().unwrap(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn panic() -> Result<()> {
+        let mut policy = RetryPolicy::new(3);
+        policy.backoff(Duration::ZERO, Duration::ZERO);
+        let (sender, receiver) = mpsc::channel();
+        let retrier = Retrier::new(
+            policy,
+            |records| {
+                assert!(!records.is_empty(), "empty records");
+                Err(Error::NotInitialized)
+            },
+            move |_, records| {
+                assert!(records.len() < 2, "too many records");
+                let _ = sender.send(records);
+            },
+        )?;
+        retrier.retry(Vec::new());
+        retrier.retry(vec![BufferedRecord::new(log::Level::Error, "", "1")]);
+        retrier.retry(vec![BufferedRecord::new(log::Level::Error, "", "2"); 2]);
+        retrier.retry(vec![BufferedRecord::new(log::Level::Error, "", "3")]);
+        let mut given_up = Vec::new();
+        while let Ok(records) = receiver.recv_timeout(Duration::from_millis(500)) {
+            given_up.extend(records);
+        }
+        given_up.sort_by(|a, b| a.args.cmp(&b.args));
+        assert_eq!(
+            given_up,
+            [
+                BufferedRecord::new(log::Level::Error, "", "1"),
+                BufferedRecord::new(log::Level::Error, "", "3"),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn flush() -> Result<()> {
+        let mut policy = RetryPolicy::new(3);
+        policy.backoff(Duration::from_secs(60 * 60), Duration::from_secs(60 * 60));
+        let shown = Arc::new(Mutex::new(Vec::new()));
+        let shown_clone = shown.clone();
+        let retrier = Retrier::new(
+            policy,
+            move |records| {
+                thread::sleep(Duration::from_millis(200));
+                shown_clone.lock().unwrap().extend_from_slice(records);
+                Ok(())
+            },
+            |_, _| {},
+        )?;
+        retrier.retry(vec![BufferedRecord::new(log::Level::Error, "", "1")]);
+        assert!(retrier.flush(Duration::from_secs(5)).is_empty());
+        assert_eq!(shown.lock().unwrap().len(), 1);
+
+        // Records not attempted within the timeout are returned.
+        retrier.retry(vec![BufferedRecord::new(log::Level::Error, "", "2")]);
+        retrier.retry(vec![BufferedRecord::new(log::Level::Error, "", "3")]);
+        assert!(!retrier.flush(Duration::from_millis(10)).is_empty());
+        Ok(())
+    }
+}
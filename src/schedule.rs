@@ -0,0 +1,137 @@
+use std::time::{Duration, SystemTime};
+
+use crate::{Error, Result};
+
+#[cfg(doc)]
+use crate::{Notification, ToastLogger};
+
+/// When and how to deliver a scheduled [`Notification`].
+///
+/// Please see [`ToastLogger::schedule()`] for how to schedule notifications,
+/// and the [`ScheduledToastNotification` class] for more details.
+///
+/// # Examples
+/// ```
+/// # use std::time::{Duration, SystemTime};
+/// # use toast_logger_win::Schedule;
+/// let mut schedule = Schedule::new(SystemTime::now() + Duration::from_secs(60 * 60));
+/// schedule
+///     .tag("retry")
+///     .snooze(Duration::from_secs(10 * 60), 3);
+/// ```
+///
+/// [`ScheduledToastNotification` class]: https://learn.microsoft.com/uwp/api/windows.ui.notifications.scheduledtoastnotification
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Schedule {
+    pub(crate) delivery_time: SystemTime,
+    pub(crate) snooze: Option<(Duration, u32)>,
+    pub(crate) tag: String,
+    pub(crate) group: String,
+}
+
+impl Schedule {
+    /// The minimum snooze interval allowed by Windows.
+    pub const MIN_SNOOZE_INTERVAL: Duration = Duration::from_secs(60);
+    /// The maximum snooze interval allowed by Windows.
+    pub const MAX_SNOOZE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+    /// The maximum snooze count allowed by Windows.
+    pub const MAX_SNOOZE_COUNT: u32 = 5;
+
+    /// Construct with the time to deliver the notification.
+    pub fn new(delivery_time: SystemTime) -> Self {
+        Self {
+            delivery_time,
+            snooze: None,
+            tag: String::new(),
+            group: String::new(),
+        }
+    }
+
+    /// Set the tag to identify the scheduled notification.
+    /// The tag can be used to [cancel] the scheduled notification.
+    ///
+    /// [cancel]: ToastLogger::cancel_scheduled()
+    pub fn tag(&mut self, tag: &str) -> &mut Self {
+        self.tag = tag.into();
+        self
+    }
+
+    /// Set the group to identify the scheduled notification.
+    pub fn group(&mut self, group: &str) -> &mut Self {
+        self.group = group.into();
+        self
+    }
+
+    /// Show the notification again after the `interval`,
+    /// up to `max_count` times.
+    ///
+    /// The `interval` must be between [`Schedule::MIN_SNOOZE_INTERVAL`]
+    /// and [`Schedule::MAX_SNOOZE_INTERVAL`],
+    /// and the `max_count` must be between 1 and [`Schedule::MAX_SNOOZE_COUNT`].
+    pub fn snooze(&mut self, interval: Duration, max_count: u32) -> &mut Self {
+        self.snooze = Some((interval, max_count));
+        self
+    }
+
+    pub(crate) fn validate(&self) -> Result<()> {
+        if self.delivery_time <= SystemTime::now() {
+            return Err(Error::InvalidSchedule("the delivery time is in the past"));
+        }
+        if let Some((interval, max_count)) = self.snooze {
+            if !(Self::MIN_SNOOZE_INTERVAL..=Self::MAX_SNOOZE_INTERVAL).contains(&interval) {
+                return Err(Error::InvalidSchedule(
+                    "the snooze interval is out of range",
+                ));
+            }
+            if !(1..=Self::MAX_SNOOZE_COUNT).contains(&max_count) {
+                return Err(Error::InvalidSchedule("the snooze count is out of range"));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A pending scheduled notification.
+///
+/// Please see [`ToastLogger::scheduled()`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScheduledEntry {
+    pub tag: String,
+    pub group: String,
+    pub delivery_time: SystemTime,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate() {
+        let future = SystemTime::now() + Duration::from_secs(60);
+        assert!(Schedule::new(future).validate().is_ok());
+        assert!(Schedule::new(SystemTime::UNIX_EPOCH).validate().is_err());
+
+        let minute = Duration::from_secs(60);
+        assert!(Schedule::new(future).snooze(minute, 1).validate().is_ok());
+        assert!(
+            Schedule::new(future)
+                .snooze(minute * 60, 5)
+                .validate()
+                .is_ok()
+        );
+        assert!(
+            Schedule::new(future)
+                .snooze(minute / 2, 1)
+                .validate()
+                .is_err()
+        );
+        assert!(
+            Schedule::new(future)
+                .snooze(minute * 61, 1)
+                .validate()
+                .is_err()
+        );
+        assert!(Schedule::new(future).snooze(minute, 0).validate().is_err());
+        assert!(Schedule::new(future).snooze(minute, 6).validate().is_err());
+    }
+}
@@ -0,0 +1,145 @@
+use std::{
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::SystemTime,
+};
+
+use crate::{BufferedRecord, Error, sync::IgnorePoison};
+
+#[cfg(doc)]
+use crate::{ToastLogger, ToastLoggerBuilder};
+
+/// Statistics of the [`ToastLogger`] since it was created.
+///
+/// Please see [`ToastLogger::stats()`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// The number of records passed to the logger.
+    /// Records filtered out by [`log::max_level()`] are not counted.
+    pub records_seen: u64,
+    /// The number of records filtered out by the filters of the logger.
+    pub records_filtered: u64,
+    /// The number of records appended to the buffer
+    /// when not [auto-flushing].
+    ///
+    /// [auto-flushing]: ToastLoggerBuilder::auto_flush()
+    pub records_buffered: u64,
+    /// The number of records in the shown notifications.
+    /// The summaries of omitted records, such as
+    /// "…and 3 more messages dropped", are not counted.
+    /// Records merged by [`ToastLoggerBuilder::dedup()`] are counted once.
+    pub records_shown: u64,
+    /// The number of repetitions merged by [`ToastLoggerBuilder::dedup()`]
+    /// into the records in the shown notifications.
+    pub records_deduplicated: u64,
+    /// The number of records suppressed by
+    /// [`ToastLoggerBuilder::rate_limit()`],
+    /// including their merged repetitions.
+    pub records_rate_limited: u64,
+    /// The number of records dropped by
+    /// [`ToastLoggerBuilder::buffer_capacity()`],
+    /// including their merged repetitions.
+    pub records_dropped: u64,
+    /// The number of records failed to show,
+    /// including their merged repetitions.
+    pub records_failed: u64,
+    /// The number of records logged from the callbacks of the logger,
+    /// such as the formatter, on the same thread.
+    /// They are written to the standard error instead of recursing.
+    pub records_reentrant: u64,
+    /// The number of notifications shown.
+    pub notifications_shown: u64,
+    /// The time of the last notification shown.
+    pub last_delivery: Option<SystemTime>,
+    /// The time and the message of the last error.
+    pub last_error: Option<(SystemTime, String)>,
+}
+
+/// The counters to create [`Stats`].
+///
+/// The counters owned by other components,
+/// such as the number of dropped records, are added by the logger.
+#[derive(Debug, Default)]
+pub(crate) struct StatsCounters {
+    seen: AtomicU64,
+    filtered: AtomicU64,
+    buffered: AtomicU64,
+    shown: AtomicU64,
+    deduplicated: AtomicU64,
+    failed: AtomicU64,
+    reentrant: AtomicU64,
+    notifications: AtomicU64,
+    last_delivery: Mutex<Option<SystemTime>>,
+    last_error: Mutex<Option<(SystemTime, String)>>,
+}
+
+impl StatsCounters {
+    pub fn seen(&self) {
+        self.seen.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn filtered(&self) {
+        self.filtered.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn buffered(&self, count: usize) {
+        self.buffered.fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    pub fn shown(&self, records: &[BufferedRecord]) {
+        let shown = Self::count_logged(records);
+        self.shown.fetch_add(shown, Ordering::Relaxed);
+        // Repetitions are counted only when shown,
+        // so that dropped or failed ones are not counted twice.
+        self.deduplicated
+            .fetch_add(Self::count_repeated(records) - shown, Ordering::Relaxed);
+        self.notifications.fetch_add(1, Ordering::Relaxed);
+        *self.last_delivery.lock().ignore_poison() = Some(SystemTime::now());
+    }
+
+    pub fn failed(&self, records: &[BufferedRecord]) {
+        self.failed
+            .fetch_add(Self::count_repeated(records), Ordering::Relaxed);
+    }
+
+    pub fn reentrant(&self) {
+        self.reentrant.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn error(&self, error: &Error) {
+        *self.last_error.lock().ignore_poison() = Some((SystemTime::now(), error.to_string()));
+    }
+
+    /// The number of the `records` excluding the summaries.
+    fn count_logged(records: &[BufferedRecord]) -> u64 {
+        records.iter().filter(|record| !record.is_summary).count() as u64
+    }
+
+    /// The number of the `records` with their repetitions,
+    /// excluding the summaries.
+    fn count_repeated(records: &[BufferedRecord]) -> u64 {
+        records
+            .iter()
+            .filter(|record| !record.is_summary)
+            .map(|record| record.count.max(1) as u64)
+            .sum()
+    }
+
+    pub fn snapshot(&self) -> Stats {
+        Stats {
+            records_seen: self.seen.load(Ordering::Relaxed),
+            records_filtered: self.filtered.load(Ordering::Relaxed),
+            records_buffered: self.buffered.load(Ordering::Relaxed),
+            records_shown: self.shown.load(Ordering::Relaxed),
+            records_deduplicated: self.deduplicated.load(Ordering::Relaxed),
+            records_failed: self.failed.load(Ordering::Relaxed),
+            records_reentrant: self.reentrant.load(Ordering::Relaxed),
+            notifications_shown: self.notifications.load(Ordering::Relaxed),
+            last_delivery: *self.last_delivery.lock().ignore_poison(),
+            last_error: self.last_error.lock().ignore_poison().clone(),
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use std::{
+    cell::Cell,
+    sync::{LockResult, PoisonError},
+};
+
+/// Recover the lock even if another thread panicked while holding it,
+/// such as in a user callback.
+///
+/// The states protected by the locks are kept consistent
+/// at the points where the callbacks are called.
+pub(crate) trait IgnorePoison<T> {
+    fn ignore_poison(self) -> T;
+}
+
+impl<T> IgnorePoison<T> for LockResult<T> {
+    fn ignore_poison(self) -> T {
+        self.unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+thread_local! {
+    static IS_ENTERED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Detects logs from the callbacks of the logger on the same thread,
+/// such as a formatter calling [`log::error!`],
+/// which would recurse or deadlock.
+#[derive(Debug)]
+pub(crate) struct ReentrancyGuard {
+    _private: (),
+}
+
+impl ReentrancyGuard {
+    /// Returns `None` if the current thread is already in the logger.
+    pub fn enter() -> Option<Self> {
+        if IS_ENTERED.replace(true) {
+            return None;
+        }
+        Some(Self { _private: () })
+    }
+}
+
+impl Drop for ReentrancyGuard {
+    fn drop(&mut self) {
+        IS_ENTERED.set(false);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    #[test]
+    fn reentrancy_guard() {
+        let guard = ReentrancyGuard::enter();
+        assert!(guard.is_some());
+        assert!(ReentrancyGuard::enter().is_none());
+        std::thread::spawn(|| assert!(ReentrancyGuard::enter().is_some()))
+            .join()
+            .unwrap();
+        drop(guard);
+        assert!(ReentrancyGuard::enter().is_some());
+    }
+
+    #[test]
+    fn ignore_poison() {
+        let mutex = Arc::new(Mutex::new(1));
+        let mutex_clone = mutex.clone();
+        let _ = std::thread::spawn(move || {
+            let _lock = mutex_clone.lock().unwrap();
+            panic!("poison");
+        })
+        .join();
+        assert!(mutex.is_poisoned());
+        assert_eq!(*mutex.lock().ignore_poison(), 1);
+    }
+}
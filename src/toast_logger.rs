@@ -1,33 +1,92 @@
 use std::{
-    fmt, mem,
-    sync::{Mutex, OnceLock},
+    collections::{BTreeMap, HashMap},
+    env, fmt, mem,
+    panic::{self, Location, PanicHookInfo},
+    sync::{
+        Arc, Mutex, OnceLock, RwLock, Weak,
+        atomic::{AtomicBool, Ordering},
+        mpsc,
+    },
+    thread,
+    time::{Duration, Instant},
 };
 
 use log::Log;
 
-use crate::{BufferedRecord, Notification, Notifier, Result};
+use crate::{
+    BufferedRecord, DismissalReason, Error, HistoryEntry, HistoryRemoval, Notification, Notifier,
+    NotifierBackend, OverflowPolicy, Result, RetryPolicy, Schedule, ScheduledEntry, Stats,
+    batch::Batcher, breadcrumbs::Breadcrumbs, buffer::RecordBuffer, dedup::Deduplicator,
+    events::NotificationEvents, exit, filter::Filter, limits::ContentLimits,
+    rate_limit::RateLimiter, retry::Retrier, stats::StatsCounters, sync::IgnorePoison,
+    sync::ReentrancyGuard, worker::Worker,
+};
 
 type LogRecordFormatter =
     dyn Fn(&mut dyn fmt::Write, &log::Record) -> fmt::Result + Send + Sync + 'static;
 type NotificationCreator =
     dyn Fn(&[BufferedRecord]) -> Result<Notification> + Send + Sync + 'static;
+type MetadataFilter = dyn Fn(&log::Metadata) -> bool + Send + Sync + 'static;
+type RecordFilter = dyn Fn(&log::Record) -> bool + Send + Sync + 'static;
+type ErrorHandler = dyn Fn(&Error, &[BufferedRecord]) + Send + Sync + 'static;
 
+#[derive(Clone)]
 struct ToastLoggerConfig {
-    max_level: log::LevelFilter,
+    filter: Filter,
+    metadata_filter: Option<Arc<MetadataFilter>>,
+    record_filter: Option<Arc<RecordFilter>>,
+    popup_level: log::LevelFilter,
     is_auto_flush: bool,
+    breadcrumbs: Option<(log::Level, usize)>,
+    batch: Option<(Duration, Duration)>,
+    rate_limit: Option<(u32, Duration)>,
+    target_rate_limit: Option<(u32, Duration)>,
+    dedup: Option<Duration>,
+    buffer_capacity: Option<usize>,
+    overflow_policy: OverflowPolicy,
+    content_limits: ContentLimits,
+    is_background_delivery: bool,
+    retry: Option<RetryPolicy>,
+    flush_timeout: Duration,
+    is_flush_on_exit: bool,
+    is_flush_on_console_events: bool,
+    is_panic_hook: bool,
     application_id: String,
-    formatter: Box<LogRecordFormatter>,
-    create_notification: Box<NotificationCreator>,
+    expirations: BTreeMap<log::Level, Duration>,
+    formatter: Arc<LogRecordFormatter>,
+    create_notification: Arc<NotificationCreator>,
+    events: NotificationEvents,
+    on_error: Option<Arc<ErrorHandler>>,
 }
 
 impl Default for ToastLoggerConfig {
     fn default() -> Self {
         Self {
-            max_level: log::LevelFilter::Error,
+            filter: Filter::new(log::LevelFilter::Error),
+            metadata_filter: None,
+            record_filter: None,
+            popup_level: log::LevelFilter::Trace,
             is_auto_flush: true,
+            breadcrumbs: None,
+            batch: None,
+            rate_limit: None,
+            target_rate_limit: None,
+            dedup: None,
+            buffer_capacity: None,
+            overflow_policy: OverflowPolicy::default(),
+            content_limits: ContentLimits::default(),
+            is_background_delivery: false,
+            retry: None,
+            flush_timeout: Self::DEFAULT_FLUSH_TIMEOUT,
+            is_flush_on_exit: false,
+            is_flush_on_console_events: false,
+            is_panic_hook: false,
             application_id: Self::DEFAULT_APP_ID.into(),
-            formatter: Box::new(Self::default_formatter),
-            create_notification: Box::new(Notification::new_with_records),
+            expirations: BTreeMap::new(),
+            formatter: Arc::new(Self::default_formatter),
+            create_notification: Arc::new(Notification::new_with_records),
+            events: NotificationEvents::default(),
+            on_error: None,
         }
     }
 }
@@ -36,6 +95,7 @@ impl ToastLoggerConfig {
     // https://github.com/GitHub30/toast-notification-examples
     const DEFAULT_APP_ID: &str =
         r"{1AC14E77-02E7-4E5D-B744-2EB1AE5198B7}\WindowsPowerShell\v1.0\powershell.exe";
+    const DEFAULT_FLUSH_TIMEOUT: Duration = Duration::from_secs(5);
 
     fn default_formatter(buf: &mut dyn fmt::Write, record: &log::Record) -> fmt::Result {
         write!(buf, "{}: {}", record.level(), record.args())
@@ -44,6 +104,37 @@ impl ToastLoggerConfig {
     fn create_notifier(&self) -> Result<Notifier> {
         Notifier::new_with_application_id(&self.application_id)
     }
+
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        self.filter.enabled(metadata)
+            && self
+                .metadata_filter
+                .as_ref()
+                .is_none_or(|filter| filter(metadata))
+    }
+
+    fn matches(&self, record: &log::Record) -> bool {
+        self.filter.matches(record)
+            && self
+                .record_filter
+                .as_ref()
+                .is_none_or(|filter| filter(record))
+    }
+
+    /// The default expiration for the most severe level in `records`.
+    fn expiration_for(&self, records: &[BufferedRecord]) -> Option<Duration> {
+        let level = records.iter().map(|r| r.level).min()?;
+        self.expirations.get(&level).copied()
+    }
+
+    /// Whether the notification for `records` should show the popup.
+    fn shows_popup(&self, records: &[BufferedRecord]) -> bool {
+        records
+            .iter()
+            .map(|r| r.level)
+            .min()
+            .is_none_or(|level| level <= self.popup_level)
+    }
 }
 
 /// Builder for [`ToastLogger`].
@@ -62,19 +153,77 @@ pub struct ToastLoggerBuilder {
 }
 
 impl ToastLoggerBuilder {
+    /// The default name of the environment variable for [`from_env()`].
+    ///
+    /// [`from_env()`]: ToastLoggerBuilder::from_env()
+    pub const DEFAULT_ENV: &str = "TOAST_LOG";
+
     fn new() -> Self {
         Self::default()
     }
 
     /// Initialize the [`log`] crate to use the [`ToastLogger`]
     /// with the configurations set to this builder.
-    pub fn init(&mut self) -> Result<()> {
+    ///
+    /// Returns a [`ToastLoggerHandle`] to change the configurations later.
+    /// # Panics
+    /// Panics if the [`ToastLogger`] is already initialized.
+    /// Please see [`try_init()`] for the non-panicking version.
+    ///
+    /// [`try_init()`]: ToastLoggerBuilder::try_init()
+    pub fn init(&mut self) -> Result<ToastLoggerHandle> {
+        match self.try_init() {
+            Err(Error::AlreadyInitialized) => panic!("ToastLogger already initialized."),
+            result => result,
+        }
+    }
+
+    /// Same as [`init()`], except that this returns
+    /// [`Error::AlreadyInitialized`] if the [`ToastLogger`] is already initialized.
+    ///
+    /// Nothing global, including [`log::max_level()`], is changed on errors.
+    /// # Examples
+    /// Libraries and tests can initialize idempotently by:
+    /// ```no_run
+    /// # use toast_logger_win::{Error, Result, ToastLogger};
+    /// # fn test() -> Result<()> {
+    /// match ToastLogger::builder().try_init() {
+    ///     Ok(_) | Err(Error::AlreadyInitialized) => {}
+    ///     Err(error) => return Err(error),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`init()`]: ToastLoggerBuilder::init()
+    pub fn try_init(&mut self) -> Result<ToastLoggerHandle> {
         ToastLogger::init(self.build_config())
     }
 
+    /// Same as [`init()`], and returns a [`FlushGuard`]
+    /// that flushes the logs when it's dropped.
+    ///
+    /// To keep the [`ToastLoggerHandle`] too,
+    /// call [`ToastLoggerHandle::flush_guard()`] instead.
+    /// # Examples
+    /// ```no_run
+    /// # use toast_logger_win::{Result, ToastLogger};
+    /// fn main() -> Result<()> {
+    ///     let _guard = ToastLogger::builder().auto_flush(false).init_with_guard()?;
+    ///     log::info!("This is shown even if `main` returns early.");
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// [`init()`]: ToastLoggerBuilder::init()
+    pub fn init_with_guard(&mut self) -> Result<FlushGuard> {
+        Ok(self.init()?.flush_guard())
+    }
+
     #[deprecated(since = "0.2.0", note = "Use `init()` instead")]
     pub fn init_logger(&mut self) -> Result<()> {
-        self.init()
+        self.init()?;
+        Ok(())
     }
 
     /// Build a `ToastLogger`.
@@ -91,8 +240,104 @@ impl ToastLoggerBuilder {
 
     /// Set the maximum level of logs to be displayed.
     /// Logs above the specified level are discarded.
+    ///
+    /// This is the default level for targets
+    /// without matching [filter directives].
+    ///
+    /// [filter directives]: ToastLoggerBuilder::parse_filters()
     pub fn max_level(&mut self, level: log::LevelFilter) -> &mut Self {
-        self.config.max_level = level;
+        self.config.filter.set_default_level(level);
+        self
+    }
+
+    /// Set the maximum level of logs for the targets
+    /// starting with the `target`.
+    ///
+    /// When multiple targets match,
+    /// the longest one wins.
+    /// Targets without matching directives use the [`max_level()`].
+    /// # Examples
+    /// ```no_run
+    /// # use toast_logger_win::{Result, ToastLogger};
+    /// # fn test() -> Result<()> {
+    /// ToastLogger::builder()
+    ///     .max_level(log::LevelFilter::Warn)
+    ///     .filter_target("my_app::net", log::LevelFilter::Info)
+    ///     .filter_target("hyper", log::LevelFilter::Off)
+    ///     .init()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`max_level()`]: ToastLoggerBuilder::max_level()
+    pub fn filter_target(&mut self, target: &str, level: log::LevelFilter) -> &mut Self {
+        self.config.filter.set_target_level(target, level);
+        self
+    }
+
+    /// Parse filter directives in the [`env_logger` syntax],
+    /// and add them to the filters.
+    ///
+    /// The `filters` is a comma-separated list of directives.
+    /// Each directive is one of:
+    /// * `level` sets the [`max_level()`].
+    /// * `target=level` sets the level for the targets
+    ///   starting with the `target`.
+    ///   Please see [`filter_target()`] for more details.
+    /// * `target` enables all levels for the `target`.
+    ///
+    /// The list can be followed by `/` and a regular expression.
+    /// When it's present,
+    /// only logs whose messages match the regular expression are shown.
+    /// The regular expression requires the feature `regex`.
+    /// Without it, the string is matched as a substring,
+    /// in the same way as [`env_logger`].
+    ///
+    /// Returns [`Error::InvalidFilter`] if the regular expression is invalid.
+    /// # Examples
+    /// ```no_run
+    /// # use toast_logger_win::{Result, ToastLogger};
+    /// # fn test() -> Result<()> {
+    /// ToastLogger::builder()
+    ///     .parse_filters("warn,my_app::net=info,hyper=off")?
+    ///     .init()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`env_logger` syntax]: https://docs.rs/env_logger/latest/env_logger/#enabling-logging
+    /// [`env_logger`]: https://docs.rs/env_logger/latest/env_logger/#filtering-results
+    /// [`filter_target()`]: ToastLoggerBuilder::filter_target()
+    /// [`max_level()`]: ToastLoggerBuilder::max_level()
+    pub fn parse_filters(&mut self, filters: &str) -> Result<&mut Self> {
+        self.config.filter.parse(filters)?;
+        Ok(self)
+    }
+
+    /// Set the maximum level of logs that show popups.
+    /// Notifications for logs above the specified level are
+    /// delivered silently to the Action Center without popups.
+    /// Please see [`Notification::suppress_popup()`] for more details.
+    ///
+    /// When a notification contains multiple records,
+    /// the most severe level is used.
+    /// The default value is [`log::LevelFilter::Trace`],
+    /// which shows popups for all logs.
+    /// # Examples
+    /// ```no_run
+    /// # use toast_logger_win::{Result, ToastLogger};
+    /// # fn test() -> Result<()> {
+    /// ToastLogger::builder()
+    ///     .max_level(log::LevelFilter::Info)
+    ///     .popup_level(log::LevelFilter::Error)
+    ///     .init()?;
+    /// log::info!("This goes to the Action Center silently.");
+    /// log::error!("This shows a popup.");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn popup_level(&mut self, level: log::LevelFilter) -> &mut Self {
+        self.config.popup_level = level;
         self
     }
 
@@ -115,7 +360,7 @@ impl ToastLoggerBuilder {
     ///     .init()?;
     /// log::info!("Test info log");
     /// log::info!("Test info log 2");
-    /// ToastLogger::flush()?;  // Shows only one notification with both logs.
+    /// ToastLogger::instance()?.flush()?;  // Shows only one notification with both logs.
     /// #  Ok(())
     /// # }
     /// ```
@@ -124,200 +369,1680 @@ impl ToastLoggerBuilder {
         self
     }
 
-    /// Set the application ID for the Toast Notification.
+    /// Keep logs below the `trigger` level as the context,
+    /// and show them only when a log at or above the `trigger` level arrives.
     ///
-    /// This is the application ID passed to the Windows [`CreateToastNotifier`] API.
-    /// Please also see the [Application User Model ID][AUMID],
-    /// and the "[Find the Application User Model ID of an installed app]".
+    /// Up to `capacity` most recent logs below the `trigger` level
+    /// are kept without being shown.
+    /// When a log at or above the `trigger` level arrives,
+    /// they are shown together with the log in one notification.
+    /// When [`auto_flush()`] is `false`,
+    /// they are appended to the internal buffer instead.
     ///
-    /// [AUMID]: https://learn.microsoft.com/windows/win32/shell/appids
-    /// [`CreateToastNotifier`]: https://learn.microsoft.com/uwp/api/windows.ui.notifications.toastnotificationmanager.createtoastnotifier#windows-ui-notifications-toastnotificationmanager-createtoastnotifier(system-string)
-    /// [Find the Application User Model ID of an installed app]: https://learn.microsoft.com/windows/configuration/find-the-application-user-model-id-of-an-installed-app
-    pub fn application_id(&mut self, application_id: &str) -> &mut Self {
-        self.config.application_id = application_id.into();
+    /// Note that logs must be enabled by the [`max_level()`]
+    /// to be kept as the context.
+    /// # Examples
+    /// ```no_run
+    /// # use toast_logger_win::{Result, ToastLogger};
+    /// # fn test() -> Result<()> {
+    /// ToastLogger::builder()
+    ///     .max_level(log::LevelFilter::Info)
+    ///     .breadcrumbs(log::Level::Error, 5)
+    ///     .init()?;
+    /// log::info!("Connecting...");  // Not shown.
+    /// log::error!("Connection failed.");  // Shows both logs.
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`auto_flush()`]: ToastLoggerBuilder::auto_flush()
+    /// [`max_level()`]: ToastLoggerBuilder::max_level()
+    pub fn breadcrumbs(&mut self, trigger: log::Level, capacity: usize) -> &mut Self {
+        self.config.breadcrumbs = Some((trigger, capacity));
         self
     }
 
-    // https://docs.rs/env_logger/0.11.8/env_logger/#using-a-custom-format
-    /// Set a custom formatter function
-    /// that writes [`log::Record`] to [`fmt::Write`].
+    /// Collect logs until no logs arrive for the `quiet` period,
+    /// and then show them in one notification.
     ///
-    /// The default formatter writes the logs with their levels as prefixes.
+    /// The logs are shown at the latest after the `max_latency`
+    /// from the first log,
+    /// even when logs keep arriving.
+    /// A background thread owns the timer,
+    /// and the [`auto_flush()`] is ignored when this is set.
+    /// [`ToastLogger::flush()`] can still show the logs immediately.
     /// # Examples
     /// ```no_run
-    /// # use std::fmt;
+    /// # use std::time::Duration;
     /// # use toast_logger_win::{Result, ToastLogger};
     /// # fn test() -> Result<()> {
     /// ToastLogger::builder()
-    ///     .format(|buf: &mut dyn fmt::Write, record: &log::Record| {
-    ///         match record.level() {
-    ///             log::Level::Info => buf.write_fmt(*record.args()),
-    ///             _ => write!(buf, "{}: {}", record.level(), record.args()),
-    ///         }
-    ///     })
+    ///     .batch(Duration::from_millis(200), Duration::from_secs(2))
     ///     .init()?;
+    /// for i in 0..10 {
+    ///     log::error!("Error {i}");  // Shows only one notification.
+    /// }
     /// # Ok(())
     /// # }
     /// ```
-    pub fn format<F>(&mut self, formatter: F) -> &mut Self
-    where
-        F: Fn(&mut dyn fmt::Write, &log::Record) -> fmt::Result + Send + Sync + 'static,
-    {
-        self.config.formatter = Box::new(formatter);
+    ///
+    /// [`auto_flush()`]: ToastLoggerBuilder::auto_flush()
+    pub fn batch(&mut self, quiet: Duration, max_latency: Duration) -> &mut Self {
+        self.config.batch = Some((quiet, max_latency));
         self
     }
 
-    /// Set a custom function to create the [`Notification`].
+    /// Show at most `count` notifications per `period`.
+    ///
+    /// Notifications are allowed in bursts of up to `count`,
+    /// and the allowance refills evenly over the `period`.
+    /// Records exceeding the limit are not shown but counted,
+    /// and the next notification shows how many were suppressed,
+    /// such as "…and 37 more messages suppressed".
+    /// Records merged by [`dedup()`] count all their occurrences.
     /// # Examples
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use toast_logger_win::{Result, ToastLogger};
+    /// # fn test() -> Result<()> {
+    /// ToastLogger::builder()
+    ///     .rate_limit(5, Duration::from_secs(60))
+    ///     .target_rate_limit(2, Duration::from_secs(60))
+    ///     .init()?;
+    /// # Ok(())
+    /// # }
     /// ```
-    /// # use toast_logger_win::{BufferedRecord, Notification, ToastLogger};
-    /// let builder = ToastLogger::builder()
-    ///     .create_notification(|records: &[BufferedRecord]| {
-    ///         let notification = Notification::new_with_records(records);
-    ///         // Change properties of `notification` as needed.
-    ///         notification
-    ///     });
-    /// ```
-    pub fn create_notification<F>(&mut self, create: F) -> &mut Self
-    where
-        F: Fn(&[BufferedRecord]) -> Result<Notification> + Send + Sync + 'static,
-    {
-        self.config.create_notification = Box::new(create);
+    ///
+    /// [`dedup()`]: ToastLoggerBuilder::dedup()
+    pub fn rate_limit(&mut self, count: u32, period: Duration) -> &mut Self {
+        self.config.rate_limit = Some((count, period));
         self
     }
-}
-
-/// [`log`] crate logger that
-/// implements [`log::Log`] trait.
-/// It sends the logging output to the [Windows Toast Notifications].
-///
-/// # Examples
-/// ```no_run
-/// # use toast_logger_win::{Result, ToastLogger};
-/// # pub fn test() -> Result<()> {
-///   ToastLogger::builder()
-///       .max_level(log::LevelFilter::Info)
-///       .init()?;
-///   log::info!("Test info log");  // Shows a Windows Toast Notification.
-/// #  Ok(())
-/// # }
-/// ```
-/// [Windows Toast Notifications]: https://learn.microsoft.com/windows/apps/design/shell/tiles-and-notifications/toast-notifications-overview
-pub struct ToastLogger {
-    config: ToastLoggerConfig,
-    notifier: Notifier,
-    records: Mutex<Vec<BufferedRecord>>,
-}
-
-static INSTANCE: OnceLock<ToastLogger> = OnceLock::new();
-
-impl ToastLogger {
-    /// Returns a [`ToastLoggerBuilder`] instance
-    /// that can build a [`ToastLogger`] with various configurations.
-    pub fn builder() -> ToastLoggerBuilder {
-        ToastLoggerBuilder::new()
-    }
-
-    fn init(config: ToastLoggerConfig) -> Result<()> {
-        log::set_max_level(config.max_level);
-        if INSTANCE.set(Self::new(config)?).is_err() {
-            panic!("ToastLogger already initialized.");
-        }
-        log::set_logger(INSTANCE.get().unwrap())?;
-        Ok(())
-    }
 
-    fn new(config: ToastLoggerConfig) -> Result<Self> {
-        let notifier = config.create_notifier()?;
-        Ok(Self {
-            config,
-            notifier,
-            records: Mutex::new(Vec::new()),
-        })
+    /// Show at most `count` notifications per `period` for each target.
+    ///
+    /// Records of a target exceeding the limit are suppressed
+    /// while records of other targets are still shown.
+    /// Please see [`rate_limit()`] for more details.
+    ///
+    /// [`rate_limit()`]: ToastLoggerBuilder::rate_limit()
+    pub fn target_rate_limit(&mut self, count: u32, period: Duration) -> &mut Self {
+        self.config.target_rate_limit = Some((count, period));
+        self
     }
 
-    /// Flush the internal log buffer.
-    /// If the buffer is not empty,
-    /// this function shows one toast notification
-    /// by concatenating all logs in the buffer.
+    /// Show repeated logs once with a counter such as "(×12)".
     ///
-    /// Please see [`ToastLoggerBuilder::auto_flush()`] for more details.
-    pub fn flush() -> Result<()> {
-        let logger = INSTANCE.get().ok_or_else(|| crate::Error::NotInitialized)?;
-        logger.flush_result()
+    /// Logs are repeated when their levels, targets, and messages are equal.
+    /// When [`auto_flush()`] is on,
+    /// repetitions within the `window` after a log is shown are counted,
+    /// and shown with their count on the next log after the `window`,
+    /// or on the next [flush].
+    /// When buffered, repetitions in the buffer are merged
+    /// regardless of the `window`.
+    ///
+    /// The default notification shows the counts,
+    /// and how long ago the first and the last repetitions were logged,
+    /// such as "(×12, first 5m ago, last 10s ago)".
+    /// Please see [`Notification::new_with_records()`].
+    /// The counts and the first and last timestamps are available as
+    /// [`BufferedRecord::count()`], [`BufferedRecord::first_time()`],
+    /// and [`BufferedRecord::last_time()`]
+    /// for the [`create_notification()`].
+    /// # Examples
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use toast_logger_win::{Result, ToastLogger};
+    /// # fn test() -> Result<()> {
+    /// ToastLogger::builder()
+    ///     .auto_flush(false)
+    ///     .dedup(Duration::from_secs(60))
+    ///     .init()?;
+    /// for _ in 0..12 {
+    ///     log::error!("Connection lost");
+    /// }
+    /// // Shows "ERROR: Connection lost (×12, first 0s ago, last 0s ago)".
+    /// ToastLogger::instance()?.flush()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`auto_flush()`]: ToastLoggerBuilder::auto_flush()
+    /// [`create_notification()`]: ToastLoggerBuilder::create_notification()
+    /// [flush]: ToastLogger::flush()
+    pub fn dedup(&mut self, window: Duration) -> &mut Self {
+        self.config.dedup = Some(window);
+        self
     }
 
-    fn take_records(&self) -> Option<Vec<BufferedRecord>> {
-        let mut records = self.records.lock().unwrap();
-        if records.is_empty() {
-            return None;
-        }
-        Some(mem::take(&mut *records))
+    /// Limit the number of records buffered until the next flush.
+    ///
+    /// When the buffer is full, the [`overflow_policy()`] determines
+    /// which records to keep.
+    /// The number of dropped records is shown in the next notification,
+    /// such as "…and 12 more messages dropped".
+    /// The buffer is unlimited by default.
+    /// # Examples
+    /// ```no_run
+    /// # use toast_logger_win::{OverflowPolicy, Result, ToastLogger};
+    /// # fn test() -> Result<()> {
+    /// ToastLogger::builder()
+    ///     .auto_flush(false)
+    ///     .buffer_capacity(100)
+    ///     .overflow_policy(OverflowPolicy::DropNewest)
+    ///     .init()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`overflow_policy()`]: ToastLoggerBuilder::overflow_policy()
+    pub fn buffer_capacity(&mut self, capacity: usize) -> &mut Self {
+        self.config.buffer_capacity = Some(capacity);
+        self
     }
 
-    fn log_result(&self, record: &log::Record) -> Result<()> {
-        if !self.enabled(record.metadata()) {
-            return Ok(());
-        }
-
-        let mut text = String::new();
-        (self.config.formatter)(&mut text, record)?;
-        if text.is_empty() {
-            return Ok(());
-        }
-        let buffered_record = BufferedRecord::new_with_formatted_args(record, &text);
-
-        if self.config.is_auto_flush {
-            self.show_notification(&[buffered_record])?;
-            return Ok(());
-        }
-
-        // If not auto-flushing, append to the internal buffer.
-        let mut records = self.records.lock().unwrap();
-        records.push(buffered_record);
-        Ok(())
+    /// Set what to do when the buffer is full.
+    /// The default is [`OverflowPolicy::DropOldest`].
+    ///
+    /// Please see [`buffer_capacity()`].
+    ///
+    /// [`buffer_capacity()`]: ToastLoggerBuilder::buffer_capacity()
+    pub fn overflow_policy(&mut self, policy: OverflowPolicy) -> &mut Self {
+        self.config.overflow_policy = policy;
+        self
     }
 
-    fn flush_result(&self) -> Result<()> {
-        if let Some(records) = self.take_records() {
-            return self.show_notification(&records);
-        }
-        Ok(())
+    /// Limit the number of records in a notification.
+    ///
+    /// When there are more records,
+    /// the records of the highest severity are kept first,
+    /// and then the most recent ones.
+    /// The omitted records are summarized in a footer
+    /// such as "…and 14 more (3 errors)",
+    /// or shown in follow-up notifications if [`split_notifications()`] is set.
+    /// # Examples
+    /// ```no_run
+    /// # use toast_logger_win::{Result, ToastLogger};
+    /// # fn test() -> Result<()> {
+    /// ToastLogger::builder()
+    ///     .auto_flush(false)
+    ///     .max_records(5)
+    ///     .max_record_chars(200)
+    ///     .max_notification_chars(1000)
+    ///     .init()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`split_notifications()`]: ToastLoggerBuilder::split_notifications()
+    pub fn max_records(&mut self, max: usize) -> &mut Self {
+        self.config.content_limits.max_records = Some(max);
+        self
     }
 
-    fn show_notification(&self, records: &[BufferedRecord]) -> Result<()> {
-        let notification = (self.config.create_notification)(records)?;
-        self.notifier.show(&notification)?;
-        Ok(())
+    /// Limit the number of characters of a record.
+    /// Longer records are truncated with "…".
+    ///
+    /// Please see [`max_records()`].
+    ///
+    /// [`max_records()`]: ToastLoggerBuilder::max_records()
+    pub fn max_record_chars(&mut self, max: usize) -> &mut Self {
+        self.config.content_limits.max_record_chars = Some(max);
+        self
     }
-}
 
-impl log::Log for ToastLogger {
-    fn enabled(&self, metadata: &log::Metadata) -> bool {
-        metadata.level() <= self.config.max_level
+    /// Limit the number of characters of a notification.
+    ///
+    /// Please see [`max_records()`].
+    ///
+    /// [`max_records()`]: ToastLoggerBuilder::max_records()
+    pub fn max_notification_chars(&mut self, max: usize) -> &mut Self {
+        self.config.content_limits.max_chars = Some(max);
+        self
     }
 
-    fn log(&self, record: &log::Record) {
-        if let Err(error) = self.log_result(record) {
-            eprintln!("Error while logging: {error}");
-        }
+    /// Show the records exceeding the limits in follow-up notifications,
+    /// instead of summarizing them in a footer.
+    ///
+    /// Please see [`max_records()`].
+    ///
+    /// [`max_records()`]: ToastLoggerBuilder::max_records()
+    pub fn split_notifications(&mut self, value: bool) -> &mut Self {
+        self.config.content_limits.is_split = value;
+        self
+    }
+
+    /// Create and show notifications on a background thread.
+    ///
+    /// When this is set, [`Log::log()`] only formats and queues logs,
+    /// and never blocks on the Windows notification APIs.
+    /// [`ToastLogger::flush()`] waits until the queued notifications are shown,
+    /// up to the [`flush_timeout()`].
+    /// # Examples
+    /// ```no_run
+    /// # use toast_logger_win::{Result, ToastLogger};
+    /// # fn test() -> Result<()> {
+    /// ToastLogger::builder()
+    ///     .background_delivery(true)
+    ///     .init()?;
+    /// log::error!("This returns without waiting for the notification.");
+    /// ToastLogger::instance()?.flush()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`flush_timeout()`]: ToastLoggerBuilder::flush_timeout()
+    pub fn background_delivery(&mut self, value: bool) -> &mut Self {
+        self.config.is_background_delivery = value;
+        self
+    }
+
+    /// Retry showing notifications that failed by [transient errors],
+    /// such as while the shell is restarting, or early in the logon.
+    ///
+    /// The retries run on a background thread,
+    /// so that [`Log::log()`] doesn't wait for them.
+    /// The records are passed to the [`on_error()`] handler
+    /// when the error is not transient, or all attempts failed.
+    ///
+    /// [`ToastLogger::flush()`] retries the pending records immediately,
+    /// and waits for them up to the [`flush_timeout()`].
+    /// # Examples
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use toast_logger_win::{Result, RetryPolicy, ToastLogger};
+    /// # fn test() -> Result<()> {
+    /// let mut policy = RetryPolicy::new(5);
+    /// policy.backoff(Duration::from_secs(2), Duration::from_secs(60));
+    /// ToastLogger::builder().retry(policy).init()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [transient errors]: Error::is_transient()
+    /// [`flush_timeout()`]: ToastLoggerBuilder::flush_timeout()
+    /// [`on_error()`]: ToastLoggerBuilder::on_error()
+    pub fn retry(&mut self, policy: RetryPolicy) -> &mut Self {
+        self.config.retry = Some(policy);
+        self
+    }
+
+    /// Set the maximum time [`ToastLogger::flush()`] waits
+    /// for the [`background_delivery()`] to show the notifications.
+    /// The default is 5 seconds.
+    ///
+    /// Returns [`Error::FlushTimeout`] on timeout.
+    ///
+    /// The flush by [`flush_on_exit()`] waits up to this timeout
+    /// even without the [`background_delivery()`].
+    ///
+    /// [`background_delivery()`]: ToastLoggerBuilder::background_delivery()
+    /// [`flush_on_exit()`]: ToastLoggerBuilder::flush_on_exit()
+    pub fn flush_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.config.flush_timeout = timeout;
+        self
+    }
+
+    /// Flush the logs when the process exits,
+    /// including by [`std::process::exit()`].
+    ///
+    /// The logs are flushed only once,
+    /// waiting up to the [`flush_timeout()`] for them to be shown.
+    /// This applies only to the logger installed by [`init()`].
+    /// # Examples
+    /// ```no_run
+    /// # use toast_logger_win::{Result, ToastLogger};
+    /// # fn test() -> Result<()> {
+    /// ToastLogger::builder()
+    ///     .auto_flush(false)
+    ///     .flush_on_exit(true)
+    ///     .flush_on_console_events(true)
+    ///     .init()?;
+    /// log::error!("This is shown even on `exit()` or Ctrl+C.");
+    /// std::process::exit(1);
+    /// # }
+    /// ```
+    ///
+    /// [`flush_timeout()`]: ToastLoggerBuilder::flush_timeout()
+    /// [`init()`]: ToastLoggerBuilder::init()
+    pub fn flush_on_exit(&mut self, value: bool) -> &mut Self {
+        self.config.is_flush_on_exit = value;
+        self
+    }
+
+    /// Flush the logs on Ctrl+C, Ctrl+Break,
+    /// closing the console, logoff, and shutdown.
+    /// Please see [`SetConsoleCtrlHandler`].
+    ///
+    /// Please see [`flush_on_exit()`] for more details.
+    ///
+    /// [`flush_on_exit()`]: ToastLoggerBuilder::flush_on_exit()
+    /// [`SetConsoleCtrlHandler`]: https://learn.microsoft.com/windows/console/setconsolectrlhandler
+    pub fn flush_on_console_events(&mut self, value: bool) -> &mut Self {
+        self.config.is_flush_on_console_events = value;
+        self
+    }
+
+    /// Show panics as error notifications.
+    ///
+    /// The panic message, the location, and the thread name are shown
+    /// immediately, together with the buffered logs.
+    /// The previous panic hook is called after that.
+    /// This is useful for applications without consoles,
+    /// such as ones with `#![windows_subsystem = "windows"]`.
+    ///
+    /// This applies only to the logger installed by [`init()`].
+    /// # Examples
+    /// ```no_run
+    /// # use toast_logger_win::{Result, ToastLogger};
+    /// # fn test() -> Result<()> {
+    /// ToastLogger::builder()
+    ///     .install_panic_hook(true)
+    ///     .init()?;
+    /// panic!("This is shown in a notification.");
+    /// # }
+    /// ```
+    ///
+    /// [`init()`]: ToastLoggerBuilder::init()
+    pub fn install_panic_hook(&mut self, value: bool) -> &mut Self {
+        self.config.is_panic_hook = value;
+        self
+    }
+
+    /// Set the application ID for the Toast Notification.
+    ///
+    /// This is the application ID passed to the Windows [`CreateToastNotifier`] API.
+    /// Please also see the [Application User Model ID][AUMID],
+    /// and the "[Find the Application User Model ID of an installed app]".
+    ///
+    /// [AUMID]: https://learn.microsoft.com/windows/win32/shell/appids
+    /// [`CreateToastNotifier`]: https://learn.microsoft.com/uwp/api/windows.ui.notifications.toastnotificationmanager.createtoastnotifier#windows-ui-notifications-toastnotificationmanager-createtoastnotifier(system-string)
+    /// [Find the Application User Model ID of an installed app]: https://learn.microsoft.com/windows/configuration/find-the-application-user-model-id-of-an-installed-app
+    pub fn application_id(&mut self, application_id: &str) -> &mut Self {
+        self.config.application_id = application_id.into();
+        self
+    }
+
+    /// Set the default expiration of notifications for the `level`.
+    ///
+    /// When a notification contains multiple records,
+    /// the expiration for the most severe level is used.
+    /// Notifications without the expiration for their levels don't expire.
+    /// A zero `duration` removes the expiration for the `level`.
+    ///
+    /// The expiration is applied after [`create_notification()`],
+    /// and it overrides the expiration set by the function.
+    /// Please see [`Notification::expires_in()`] for more details.
+    /// # Examples
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use toast_logger_win::{Result, ToastLogger};
+    /// # fn test() -> Result<()> {
+    /// ToastLogger::builder()
+    ///     .max_level(log::LevelFilter::Info)
+    ///     .default_expiration(log::Level::Info, Duration::from_secs(60 * 60))
+    ///     .init()?;
+    /// log::info!("This expires in an hour.");
+    /// log::error!("This doesn't expire.");
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`create_notification()`]: ToastLoggerBuilder::create_notification()
+    pub fn default_expiration(&mut self, level: log::Level, duration: Duration) -> &mut Self {
+        if duration.is_zero() {
+            self.config.expirations.remove(&level);
+        } else {
+            self.config.expirations.insert(level, duration);
+        }
+        self
+    }
+
+    /// Set a function to filter logs by their [`log::Metadata`].
+    /// Logs are shown only when the function returns `true`,
+    /// in addition to the levels and the filter directives.
+    ///
+    /// The function is also used by [`Log::enabled()`],
+    /// so that filtered logs can skip formatting.
+    /// # Examples
+    /// ```no_run
+    /// # use toast_logger_win::{Result, ToastLogger};
+    /// # fn test() -> Result<()> {
+    /// ToastLogger::builder()
+    ///     .filter(|metadata| metadata.target().starts_with("ui::"))
+    ///     .init()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn filter<F>(&mut self, filter: F) -> &mut Self
+    where
+        F: Fn(&log::Metadata) -> bool + Send + Sync + 'static,
+    {
+        self.config.metadata_filter = Some(Arc::new(filter));
+        self
+    }
+
+    /// Set a function to filter logs by their [`log::Record`].
+    /// Logs are shown only when the function returns `true`,
+    /// in addition to the [`filter()`].
+    ///
+    /// Unlike the [`filter()`], the function can see
+    /// the whole record including its message.
+    /// # Examples
+    /// ```no_run
+    /// # use toast_logger_win::{Result, ToastLogger};
+    /// # fn test() -> Result<()> {
+    /// ToastLogger::builder()
+    ///     .filter_record(|record| !record.args().to_string().is_empty())
+    ///     .init()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`filter()`]: ToastLoggerBuilder::filter()
+    pub fn filter_record<F>(&mut self, filter: F) -> &mut Self
+    where
+        F: Fn(&log::Record) -> bool + Send + Sync + 'static,
+    {
+        self.config.record_filter = Some(Arc::new(filter));
+        self
+    }
+
+    /// Read the configurations from the environment variables
+    /// whose names start with [`DEFAULT_ENV`].
+    /// Please see [`parse_env()`] for more details.
+    ///
+    /// [`DEFAULT_ENV`]: ToastLoggerBuilder::DEFAULT_ENV
+    /// [`parse_env()`]: ToastLoggerBuilder::parse_env()
+    pub fn from_env(&mut self) -> Result<&mut Self> {
+        self.parse_env(Self::DEFAULT_ENV)
+    }
+
+    /// Read the configurations from the environment variables
+    /// whose names start with the `name`.
+    ///
+    /// | Variable | Value |
+    /// | --- | --- |
+    /// | `{name}` | Filter directives. Please see [`parse_filters()`]. |
+    /// | `{name}_AUTO_FLUSH` | `true` or `false`. Please see [`auto_flush()`]. |
+    /// | `{name}_APP_ID` | Please see [`application_id()`]. |
+    /// | `{name}_EXPIRES` | Seconds, or comma-separated `level=seconds`. `0` removes the expiration. Please see [`default_expiration()`]. |
+    ///
+    /// Configurations without the environment variables
+    /// keep the values already set to this builder,
+    /// so that the environment variables can override them.
+    /// # Examples
+    /// ```no_run
+    /// # use toast_logger_win::{Result, ToastLogger};
+    /// # fn test() -> Result<()> {
+    /// // `TOAST_LOG=info` shows info logs.
+    /// ToastLogger::builder()
+    ///     .max_level(log::LevelFilter::Error)
+    ///     .parse_env("TOAST_LOG")?
+    ///     .init()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`application_id()`]: ToastLoggerBuilder::application_id()
+    /// [`auto_flush()`]: ToastLoggerBuilder::auto_flush()
+    /// [`default_expiration()`]: ToastLoggerBuilder::default_expiration()
+    /// [`parse_filters()`]: ToastLoggerBuilder::parse_filters()
+    pub fn parse_env(&mut self, name: &str) -> Result<&mut Self> {
+        self.parse_env_with(name, |name| env::var(name).ok())
+    }
+
+    fn parse_env_with(
+        &mut self,
+        name: &str,
+        var: impl Fn(&str) -> Option<String>,
+    ) -> Result<&mut Self> {
+        if let Some(filters) = var(name) {
+            self.parse_filters(&filters)?;
+        }
+
+        let auto_flush_name = format!("{name}_AUTO_FLUSH");
+        if let Some(value) = var(&auto_flush_name) {
+            let is_auto_flush = match value.trim().to_ascii_lowercase().as_str() {
+                "true" | "1" => true,
+                "false" | "0" => false,
+                _ => return Err(Error::InvalidEnv(auto_flush_name, value)),
+            };
+            self.auto_flush(is_auto_flush);
+        }
+
+        if let Some(application_id) = var(&format!("{name}_APP_ID")) {
+            self.application_id(&application_id);
+        }
+
+        let expires_name = format!("{name}_EXPIRES");
+        if let Some(value) = var(&expires_name) {
+            let invalid = || Error::InvalidEnv(expires_name.clone(), value.clone());
+            for item in value.split(',').map(str::trim) {
+                let (levels, seconds) = match item.split_once('=') {
+                    Some((level, seconds)) => {
+                        let level: log::Level = level.trim().parse().map_err(|_| invalid())?;
+                        (vec![level], seconds)
+                    }
+                    None => (log::Level::iter().collect(), item),
+                };
+                let seconds: u64 = seconds.trim().parse().map_err(|_| invalid())?;
+                for level in levels {
+                    self.default_expiration(level, Duration::from_secs(seconds));
+                }
+            }
+        }
+        Ok(self)
+    }
+
+    // https://docs.rs/env_logger/0.11.8/env_logger/#using-a-custom-format
+    /// Set a custom formatter function
+    /// that writes [`log::Record`] to [`fmt::Write`].
+    ///
+    /// The default formatter writes the logs with their levels as prefixes.
+    /// Logs formatted to empty strings are not shown,
+    /// but [`filter_record()`] is clearer to filter logs.
+    /// # Examples
+    /// ```no_run
+    /// # use std::fmt;
+    /// # use toast_logger_win::{Result, ToastLogger};
+    /// # fn test() -> Result<()> {
+    /// ToastLogger::builder()
+    ///     .format(|buf: &mut dyn fmt::Write, record: &log::Record| {
+    ///         match record.level() {
+    ///             log::Level::Info => buf.write_fmt(*record.args()),
+    ///             _ => write!(buf, "{}: {}", record.level(), record.args()),
+    ///         }
+    ///     })
+    ///     .init()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`filter_record()`]: ToastLoggerBuilder::filter_record()
+    pub fn format<F>(&mut self, formatter: F) -> &mut Self
+    where
+        F: Fn(&mut dyn fmt::Write, &log::Record) -> fmt::Result + Send + Sync + 'static,
+    {
+        self.config.formatter = Arc::new(formatter);
+        self
+    }
+
+    /// Set a custom function to create the [`Notification`].
+    /// # Examples
+    /// ```
+    /// # use toast_logger_win::{BufferedRecord, Notification, ToastLogger};
+    /// let builder = ToastLogger::builder()
+    ///     .create_notification(|records: &[BufferedRecord]| {
+    ///         let notification = Notification::new_with_records(records);
+    ///         // Change properties of `notification` as needed.
+    ///         notification
+    ///     });
+    /// ```
+    pub fn create_notification<F>(&mut self, create: F) -> &mut Self
+    where
+        F: Fn(&[BufferedRecord]) -> Result<Notification> + Send + Sync + 'static,
+    {
+        self.config.create_notification = Arc::new(create);
+        self
+    }
+
+    /// Set a function to call when the user activates a notification,
+    /// by clicking it or one of its buttons.
+    ///
+    /// The function receives the activation arguments,
+    /// the user inputs,
+    /// and the records the notification was created from.
+    /// Please see [`ToastNotification.Activated`] for more details.
+    ///
+    /// Note that notifications of unpackaged applications
+    /// can be activated only while the process is running.
+    /// # Examples
+    /// ```no_run
+    /// # use toast_logger_win::{Result, ToastLogger};
+    /// # fn test() -> Result<()> {
+    /// ToastLogger::builder()
+    ///     .on_activated(|arguments, inputs, records| {
+    ///         // The user has seen the `records`.
+    ///     })
+    ///     .init()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ToastNotification.Activated`]: https://learn.microsoft.com/uwp/api/windows.ui.notifications.toastnotification.activated
+    pub fn on_activated<F>(&mut self, handler: F) -> &mut Self
+    where
+        F: Fn(&str, &HashMap<String, String>, &[BufferedRecord]) + Send + Sync + 'static,
+    {
+        self.config.events.set_on_activated(handler);
+        self
+    }
+
+    /// Set a function to call when a notification is dismissed.
+    ///
+    /// The function receives the reason
+    /// and the records the notification was created from.
+    /// Please see [`ToastNotification.Dismissed`] for more details.
+    ///
+    /// [`ToastNotification.Dismissed`]: https://learn.microsoft.com/uwp/api/windows.ui.notifications.toastnotification.dismissed
+    pub fn on_dismissed<F>(&mut self, handler: F) -> &mut Self
+    where
+        F: Fn(DismissalReason, &[BufferedRecord]) + Send + Sync + 'static,
+    {
+        self.config.events.set_on_dismissed(handler);
+        self
+    }
+
+    /// Set a function to call when the system failed to show a notification.
+    ///
+    /// The function receives the error
+    /// and the records the notification was created from.
+    /// Please see [`ToastNotification.Failed`] for more details.
+    ///
+    /// [`ToastNotification.Failed`]: https://learn.microsoft.com/uwp/api/windows.ui.notifications.toastnotification.failed
+    pub fn on_failed<F>(&mut self, handler: F) -> &mut Self
+    where
+        F: Fn(&Error, &[BufferedRecord]) + Send + Sync + 'static,
+    {
+        self.config.events.set_on_failed(handler);
+        self
+    }
+
+    /// Set a function to handle errors while logging,
+    /// such as failures to show notifications.
+    /// The function receives the records that failed to show, if any.
+    ///
+    /// The errors are written to the standard error by default,
+    /// which is not visible in applications without consoles.
+    /// Errors returned to the callers, such as by [`ToastLogger::flush()`],
+    /// are not passed to this function.
+    /// # Examples
+    /// ```no_run
+    /// # use toast_logger_win::{BufferedRecord, Error, Result, ToastLogger};
+    /// # fn test() -> Result<()> {
+    /// ToastLogger::builder()
+    ///     .on_error(|error: &Error, records: &[BufferedRecord]| {
+    ///         // Write the `error` and the `records` to a file.
+    ///     })
+    ///     .init()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn on_error<F>(&mut self, handler: F) -> &mut Self
+    where
+        F: Fn(&Error, &[BufferedRecord]) + Send + Sync + 'static,
+    {
+        self.config.on_error = Some(Arc::new(handler));
+        self
+    }
+}
+
+/// [`log`] crate logger that
+/// implements [`log::Log`] trait.
+/// It sends the logging output to the [Windows Toast Notifications].
+///
+/// # Examples
+/// ```no_run
+/// # use toast_logger_win::{Result, ToastLogger};
+/// # pub fn test() -> Result<()> {
+///   ToastLogger::builder()
+///       .max_level(log::LevelFilter::Info)
+///       .init()?;
+///   log::info!("Test info log");  // Shows a Windows Toast Notification.
+/// #  Ok(())
+/// # }
+/// ```
+///
+/// A logger created by [`ToastLoggerBuilder::build()`] is not installed,
+/// and works independently of other loggers,
+/// such as one per plugin with a different [application ID].
+/// Clones share the same state.
+///
+/// [Windows Toast Notifications]: https://learn.microsoft.com/windows/apps/design/shell/tiles-and-notifications/toast-notifications-overview
+/// [application ID]: ToastLoggerBuilder::application_id()
+#[derive(Clone)]
+pub struct ToastLogger {
+    inner: Arc<ToastLoggerInner>,
+}
+
+/// The state of [`ToastLogger`],
+/// shared with its background threads.
+struct ToastLoggerInner {
+    config: RwLock<Arc<ToastLoggerConfig>>,
+    notifier: RwLock<Arc<dyn NotifierBackend>>,
+    records: Mutex<RecordBuffer>,
+    breadcrumbs: Option<Mutex<Breadcrumbs>>,
+    rate_limiter: Option<Mutex<RateLimiter>>,
+    deduplicator: Option<Mutex<Deduplicator>>,
+    batcher: Option<Batcher>,
+    worker: Option<Worker>,
+    retrier: Option<Retrier>,
+    stats: StatsCounters,
+}
+
+static INSTANCE: OnceLock<&'static ToastLogger> = OnceLock::new();
+
+/// Claimed by the first [`ToastLoggerBuilder::init()`]
+/// before building the logger.
+static IS_CLAIMED: AtomicBool = AtomicBool::new(false);
+
+/// The logger passed to [`log::set_logger()`].
+/// It forwards to the [`INSTANCE`] once it's built,
+/// so that the logger is built only after installing succeeds.
+struct InstalledLogger;
+
+impl log::Log for InstalledLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        INSTANCE
+            .get()
+            .is_some_and(|logger| logger.enabled(metadata))
+    }
+
+    fn log(&self, record: &log::Record) {
+        if let Some(logger) = INSTANCE.get() {
+            logger.log(record);
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(logger) = INSTANCE.get() {
+            logger.flush();
+        }
+    }
+}
+
+impl ToastLogger {
+    /// Returns a [`ToastLoggerBuilder`] instance
+    /// that can build a [`ToastLogger`] with various configurations.
+    pub fn builder() -> ToastLoggerBuilder {
+        ToastLoggerBuilder::new()
+    }
+
+    fn init(config: ToastLoggerConfig) -> Result<ToastLoggerHandle> {
+        // Claim before building the logger,
+        // so that the losers of races don't start its threads.
+        if IS_CLAIMED.swap(true, Ordering::AcqRel) {
+            return Err(Error::AlreadyInitialized);
+        }
+        let release = |error: Error| {
+            IS_CLAIMED.store(false, Ordering::Release);
+            error
+        };
+        let notifier = config.create_notifier().map_err(release)?;
+        let max_level = config.filter.max_level();
+        let logger = Self::new_with_notifier(config, Box::new(notifier)).map_err(release)?;
+        // This fails if another logger is installed.
+        log::set_logger(&InstalledLogger).map_err(|error| release(error.into()))?;
+
+        // Only the thread that installed the logger reaches here.
+        let logger: &'static ToastLogger = Box::leak(Box::new(logger));
+        let _ = INSTANCE.set(logger);
+        log::set_max_level(max_level);
+        let config = logger.inner.config();
+        if config.is_flush_on_exit {
+            exit::call_at_exit(Self::flush_on_exit);
+        }
+        if config.is_flush_on_console_events {
+            exit::call_on_console_events(Self::flush_on_exit);
+        }
+        if config.is_panic_hook {
+            let previous = panic::take_hook();
+            panic::set_hook(Box::new(move |info| {
+                Self::on_panic(info);
+                previous(info);
+            }));
+        }
+        Ok(ToastLoggerHandle {
+            inner: logger.inner.clone(),
+        })
+    }
+
+    fn on_panic(info: &PanicHookInfo) {
+        let payload = info.payload();
+        let message = payload
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("Box<dyn Any>");
+        // Panics in the callbacks of the logger are not shown,
+        // so that they don't panic again.
+        if let Ok(ToastLogger { inner: logger }) = Self::instance()
+            && let Some(_guard) = ReentrancyGuard::enter()
+            && let Err(error) = logger.show_panic(message, info.location())
+        {
+            logger.report(error);
+        }
+    }
+
+    fn flush_on_exit() {
+        if let Ok(ToastLogger { inner: logger }) = Self::instance()
+            && let Err(error) = logger.flush_on_thread(logger.config().flush_timeout)
+        {
+            logger.report(error);
+        }
+    }
+
+    fn new(config: ToastLoggerConfig) -> Result<Self> {
+        let notifier = config.create_notifier()?;
+        Self::new_with_notifier(config, Box::new(notifier))
+    }
+
+    fn new_with_notifier(
+        config: ToastLoggerConfig,
+        notifier: Box<dyn NotifierBackend>,
+    ) -> Result<Self> {
+        // `Arc::new_cyclic()` can't fail,
+        // so the error from spawning threads is returned after it.
+        let mut spawn_error = None;
+        let inner = Arc::new_cyclic(|weak: &Weak<ToastLoggerInner>| {
+            let breadcrumbs = config
+                .breadcrumbs
+                .map(|(trigger, capacity)| Mutex::new(Breadcrumbs::new(trigger, capacity)));
+            let rate_limiter = (config.rate_limit.is_some() || config.target_rate_limit.is_some())
+                .then(|| {
+                    Mutex::new(RateLimiter::new(
+                        config.rate_limit,
+                        config.target_rate_limit,
+                    ))
+                });
+            let records = RecordBuffer::new(
+                config.buffer_capacity,
+                config.overflow_policy,
+                config.dedup.is_some(),
+            );
+            let deduplicator = config
+                .dedup
+                .map(|window| Mutex::new(Deduplicator::new(window)));
+            let batcher = config.batch.and_then(|(quiet, max_latency)| {
+                let weak = weak.clone();
+                Batcher::new(quiet, max_latency, move || match weak.upgrade() {
+                    Some(inner) => {
+                        inner.flush();
+                        true
+                    }
+                    None => false,
+                })
+                .map_err(|error| spawn_error = Some(error))
+                .ok()
+            });
+            let worker = config
+                .is_background_delivery
+                .then(|| {
+                    let weak = weak.clone();
+                    Worker::new(move |records| match weak.upgrade() {
+                        Some(inner) => {
+                            if let Err(error) = inner.deliver(records) {
+                                inner.report(error);
+                            }
+                            true
+                        }
+                        None => false,
+                    })
+                    .map_err(|error| spawn_error = Some(error))
+                    .ok()
+                })
+                .flatten();
+            let retrier = config.retry.clone().and_then(|policy| {
+                let show_weak = weak.clone();
+                let give_up_weak = weak.clone();
+                Retrier::new(
+                    policy,
+                    move |records| match show_weak.upgrade() {
+                        Some(inner) => inner.show_records(records),
+                        None => Ok(()),
+                    },
+                    move |error, records| {
+                        if let Some(inner) = give_up_weak.upgrade() {
+                            inner.report(DeliveryError::new(error, records));
+                        }
+                    },
+                )
+                .map_err(|error| spawn_error = Some(error))
+                .ok()
+            });
+            ToastLoggerInner {
+                config: RwLock::new(Arc::new(config)),
+                notifier: RwLock::new(Arc::from(notifier)),
+                records: Mutex::new(records),
+                breadcrumbs,
+                rate_limiter,
+                deduplicator,
+                batcher,
+                worker,
+                retrier,
+                stats: StatsCounters::default(),
+            }
+        });
+        match spawn_error {
+            Some(error) => Err(error),
+            None => Ok(Self { inner }),
+        }
+    }
+
+    /// The logger installed by [`ToastLoggerBuilder::init()`].
+    ///
+    /// Returns [`Error::NotInitialized`] if it's not installed.
+    /// # Examples
+    /// ```no_run
+    /// # use toast_logger_win::{Result, ToastLogger};
+    /// # fn test() -> Result<()> {
+    /// ToastLogger::builder().auto_flush(false).init()?;
+    /// log::error!("Connection lost.");
+    /// ToastLogger::instance()?.flush()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn instance() -> Result<&'static ToastLogger> {
+        INSTANCE.get().copied().ok_or(Error::NotInitialized)
+    }
+
+    /// A handle to change the configurations of this logger.
+    /// Please see [`ToastLoggerHandle`].
+    ///
+    /// The handle changes [`log::max_level()`]
+    /// only if this logger is installed by [`ToastLoggerBuilder::init()`].
+    pub fn handle(&self) -> ToastLoggerHandle {
+        ToastLoggerHandle {
+            inner: self.inner.clone(),
+        }
+    }
+
+    /// Flush the internal log buffer.
+    /// If the buffer is not empty,
+    /// this function shows one toast notification
+    /// by concatenating all logs in the buffer.
+    ///
+    /// Please see [`ToastLoggerBuilder::auto_flush()`] for more details.
+    pub fn flush(&self) -> Result<()> {
+        self.inner.flush_result().map_err(|error| {
+            self.inner.stats.failed(&error.records);
+            error.error
+        })
+    }
+
+    /// The statistics of the logger, such as
+    /// the number of records shown, filtered, or failed to show.
+    ///
+    /// This helps to find why notifications were not shown.
+    /// # Examples
+    /// ```no_run
+    /// # use toast_logger_win::{Result, ToastLogger};
+    /// # fn test() -> Result<()> {
+    /// ToastLogger::builder().init()?;
+    /// let stats = ToastLogger::instance()?.stats();
+    /// if let Some((time, message)) = &stats.last_error {
+    ///     println!("Last error at {time:?}: {message}");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn stats(&self) -> Stats {
+        self.inner.stats()
+    }
+
+    /// Schedule the `notification` to be shown
+    /// at the time specified by the `schedule`.
+    ///
+    /// The [`Schedule`] can also snooze the notification,
+    /// which shows it again after an interval.
+    /// Set the [`Schedule::tag()`] to [cancel] it later.
+    /// # Examples
+    /// ```no_run
+    /// # use std::time::{Duration, SystemTime};
+    /// # use toast_logger_win::{Notification, Result, Schedule, ToastLogger};
+    /// # fn test() -> Result<()> {
+    /// ToastLogger::builder().init()?;
+    /// log::error!("Connection lost.");
+    /// let notification = Notification::new_with_text("Still disconnected?")?;
+    /// let mut schedule = Schedule::new(SystemTime::now() + Duration::from_secs(30 * 60));
+    /// schedule.tag("reconnect");
+    /// ToastLogger::instance()?.schedule(&notification, &schedule)?;
+    ///
+    /// // When reconnected.
+    /// ToastLogger::instance()?.cancel_scheduled("reconnect")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [cancel]: ToastLogger::cancel_scheduled()
+    pub fn schedule(&self, notification: &Notification, schedule: &Schedule) -> Result<()> {
+        self.inner.schedule_notification(notification, schedule)
+    }
+
+    /// The pending notifications scheduled by [`ToastLogger::schedule()`].
+    pub fn scheduled(&self) -> Result<Vec<ScheduledEntry>> {
+        self.inner.notifier().scheduled()
+    }
+
+    /// Cancel the pending scheduled notifications with the `tag`.
+    /// Returns the number of the canceled notifications.
+    pub fn cancel_scheduled(&self, tag: &str) -> Result<usize> {
+        self.inner.notifier().remove_from_schedule(tag)
+    }
+
+    /// The notifications of the [application ID] in the Action Center.
+    ///
+    /// [application ID]: ToastLoggerBuilder::application_id()
+    pub fn history(&self) -> Result<Vec<HistoryEntry>> {
+        self.inner.notifier().history()
+    }
+
+    /// Remove the shown notification with the `tag` and the `group`
+    /// from the Action Center.
+    ///
+    /// The tag and the group can be set by [`Notification::tag()`] and
+    /// [`Notification::group()`].
+    /// Use an empty string for the `group`
+    /// if the notification doesn't have a group.
+    /// Returns [`Error::EmptyTag`] if the `tag` is empty;
+    /// use [`ToastLogger::remove_shown_group()`] instead.
+    /// # Examples
+    /// ```no_run
+    /// # use toast_logger_win::{Notification, Result, ToastLogger};
+    /// # fn test() -> Result<()> {
+    /// ToastLogger::builder()
+    ///     .create_notification(|records| {
+    ///         let mut notification = Notification::new_with_records(records)?;
+    ///         notification.group("connection")?;
+    ///         Ok(notification)
+    ///     })
+    ///     .init()?;
+    /// log::error!("Connection lost.");
+    ///
+    /// // When reconnected.
+    /// ToastLogger::instance()?.remove_shown_group("connection")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn remove_shown(&self, tag: &str, group: &str) -> Result<()> {
+        if tag.is_empty() {
+            return Err(Error::EmptyTag);
+        }
+        self.inner.notifier().remove_history(&HistoryRemoval::Tag {
+            tag: tag.into(),
+            group: group.into(),
+        })
+    }
+
+    /// Remove the shown notifications in the `group`
+    /// from the Action Center.
+    /// Please see [`ToastLogger::remove_shown()`] for more details.
+    pub fn remove_shown_group(&self, group: &str) -> Result<()> {
+        self.inner
+            .notifier()
+            .remove_history(&HistoryRemoval::Group(group.into()))
+    }
+
+    /// Remove all notifications of the [application ID]
+    /// from the Action Center.
+    ///
+    /// [application ID]: ToastLoggerBuilder::application_id()
+    pub fn clear_shown(&self) -> Result<()> {
+        self.inner.notifier().remove_history(&HistoryRemoval::All)
+    }
+}
+
+/// An error with the records that failed to show.
+struct DeliveryError {
+    error: Error,
+    records: Vec<BufferedRecord>,
+}
+
+type DeliveryResult = std::result::Result<(), DeliveryError>;
+
+impl DeliveryError {
+    fn new(error: Error, records: Vec<BufferedRecord>) -> Self {
+        Self { error, records }
+    }
+}
+
+impl From<Error> for DeliveryError {
+    fn from(error: Error) -> Self {
+        Self::new(error, Vec::new())
+    }
+}
+
+impl From<fmt::Error> for DeliveryError {
+    fn from(error: fmt::Error) -> Self {
+        Error::from(error).into()
+    }
+}
+
+impl ToastLoggerInner {
+    /// A snapshot of the current configurations.
+    fn config(&self) -> Arc<ToastLoggerConfig> {
+        self.config.read().ignore_poison().clone()
+    }
+
+    fn notifier(&self) -> Arc<dyn NotifierBackend> {
+        self.notifier.read().ignore_poison().clone()
+    }
+
+    /// Whether this is the logger installed by [`ToastLoggerBuilder::init()`].
+    fn is_installed(self: &Arc<Self>) -> bool {
+        INSTANCE
+            .get()
+            .is_some_and(|logger| Arc::ptr_eq(&logger.inner, self))
+    }
+
+    /// Update the configurations atomically,
+    /// keeping [`log::max_level()`] consistent with them
+    /// if this logger is installed.
+    fn reconfigure<F>(self: &Arc<Self>, update: F)
+    where
+        F: FnOnce(&mut ToastLoggerConfig),
+    {
+        let mut config = self.config.write().ignore_poison();
+        let mut new_config = ToastLoggerConfig::clone(&config);
+        update(&mut new_config);
+        // Other loggers, such as one nested in another logger,
+        // must not change the level of the installed one.
+        if self.is_installed() {
+            log::set_max_level(new_config.filter.max_level());
+        }
+        *config = Arc::new(new_config);
+    }
+
+    fn schedule_notification(
+        &self,
+        notification: &Notification,
+        schedule: &Schedule,
+    ) -> Result<()> {
+        schedule.validate()?;
+        self.notifier().add_to_schedule(notification, schedule)
+    }
+
+    /// Take the buffered records,
+    /// and the repetitions suppressed by the [`Deduplicator`].
+    fn take_records(&self) -> Vec<BufferedRecord> {
+        let mut records = self
+            .records
+            .lock()
+            .ignore_poison()
+            .take()
+            .unwrap_or_default();
+        if let Some(deduplicator) = &self.deduplicator {
+            records.extend(deduplicator.lock().ignore_poison().take_suppressed());
+        }
+        records
+    }
+
+    fn stats(&self) -> Stats {
+        let mut stats = self.stats.snapshot();
+        {
+            let buffer = self.records.lock().ignore_poison();
+            stats.records_dropped = buffer.total_dropped();
+        }
+        if let Some(rate_limiter) = &self.rate_limiter {
+            stats.records_rate_limited = rate_limiter.lock().ignore_poison().total_suppressed();
+        }
+        stats
+    }
+
+    /// Pass the `error` to the [`ToastLoggerBuilder::on_error()`] handler.
+    fn report(&self, error: DeliveryError) {
+        let _guard = ReentrancyGuard::enter();
+        self.stats.failed(&error.records);
+        self.stats.error(&error.error);
+        match &self.config().on_error {
+            Some(handler) => handler(&error.error, &error.records),
+            None => eprintln!("Error while logging: {}", error.error),
+        }
+    }
+
+    fn log_result(&self, record: &log::Record) -> DeliveryResult {
+        self.stats.seen();
+        let config = self.config();
+        if !config.enabled(record.metadata()) || !config.matches(record) {
+            self.stats.filtered();
+            return Ok(());
+        }
+
+        let mut text = String::new();
+        (config.formatter)(&mut text, record)?;
+        if text.is_empty() {
+            self.stats.filtered();
+            return Ok(());
+        }
+        let buffered_record = BufferedRecord::new_with_formatted_args(record, &text);
+        let is_auto_flush = config.is_auto_flush && self.batcher.is_none();
+        let buffered_records = match self.deduplicator.as_ref().filter(|_| is_auto_flush) {
+            Some(deduplicator) => {
+                let mut deduplicator = deduplicator.lock().ignore_poison();
+                let now = buffered_record.last_time;
+                let shown = deduplicator.push(buffered_record);
+                // Repetitions of other records whose windows ended.
+                let mut records = deduplicator.take_expired(now);
+                records.extend(shown);
+                records
+            }
+            None => vec![buffered_record],
+        };
+        let records: Vec<_> = match &self.breadcrumbs {
+            Some(breadcrumbs) => {
+                let mut breadcrumbs = breadcrumbs.lock().ignore_poison();
+                buffered_records
+                    .into_iter()
+                    .filter_map(|record| breadcrumbs.push(record))
+                    .flatten()
+                    .collect()
+            }
+            None => buffered_records,
+        };
+        if records.is_empty() {
+            return Ok(());
+        }
+
+        if is_auto_flush {
+            self.show_notification(&records)?;
+            return Ok(());
+        }
+
+        // If not auto-flushing, append to the internal buffer.
+        // Records overflowed by `OverflowPolicy::Flush` are shown
+        // after releasing the lock.
+        let overflows: Vec<_> = {
+            let mut buffer = self.records.lock().ignore_poison();
+            self.stats.buffered(records.len());
+            records
+                .into_iter()
+                .filter_map(|record| buffer.push(record))
+                .collect()
+        };
+        if let Some(batcher) = &self.batcher {
+            batcher.notify();
+        }
+        for records in overflows {
+            self.show_notification(&records)?;
+        }
+        Ok(())
+    }
+
+    /// Show the panic with the buffered records immediately,
+    /// on the current thread.
+    ///
+    /// Only the content limits apply.
+    /// The panic is not suppressed by the rate limits,
+    /// nor retried after the process may have exited.
+    fn show_panic(&self, message: &str, location: Option<&Location>) -> DeliveryResult {
+        let thread = std::thread::current();
+        let thread_name = thread.name().unwrap_or("<unnamed>");
+        let args = match location {
+            Some(location) => format!("thread '{thread_name}' panicked at {location}:\n{message}"),
+            None => format!("thread '{thread_name}' panicked:\n{message}"),
+        };
+        let mut text = String::new();
+        (self.config().formatter)(
+            &mut text,
+            &log::Record::builder()
+                .level(log::Level::Error)
+                .target("panic")
+                .file(location.map(Location::file))
+                .line(location.map(Location::line))
+                .args(format_args!("{args}"))
+                .build(),
+        )?;
+        let mut records = self.take_records();
+        records.push(BufferedRecord::new(log::Level::Error, "panic", &text));
+        self.show_limited(&records, Self::show_records)
+    }
+
+    fn flush_result(&self) -> DeliveryResult {
+        self.flush_with_timeout(self.config().flush_timeout)
+    }
+
+    fn flush_with_timeout(&self, timeout: Duration) -> DeliveryResult {
+        let records = self.take_records();
+        if !records.is_empty() {
+            self.show_notification(&records)?;
+        }
+        let start = Instant::now();
+        if let Some(worker) = &self.worker {
+            worker.wait(timeout)?;
+        }
+        if let Some(retrier) = &self.retrier {
+            let records = retrier.flush(timeout.saturating_sub(start.elapsed()));
+            if !records.is_empty() {
+                return Err(DeliveryError::new(Error::FlushTimeout(timeout), records));
+            }
+        }
+        Ok(())
+    }
+
+    /// Same as [`ToastLoggerInner::flush_with_timeout()`],
+    /// but also bounds showing the records without the [`Worker`]
+    /// by showing them on another thread.
+    fn flush_on_thread(self: &Arc<Self>, timeout: Duration) -> DeliveryResult {
+        if self.worker.is_some() {
+            return self.flush_with_timeout(timeout);
+        }
+        let (sender, receiver) = mpsc::channel();
+        let inner = self.clone();
+        let spawned = thread::Builder::new()
+            .name("toast-logger-flush".into())
+            .spawn(move || {
+                let _ = sender.send(inner.flush_with_timeout(timeout));
+            });
+        if spawned.is_err() {
+            return self.flush_with_timeout(timeout);
+        }
+        match receiver.recv_timeout(timeout) {
+            Ok(result) => result,
+            Err(mpsc::RecvTimeoutError::Timeout) => Err(Error::FlushTimeout(timeout).into()),
+            // The panic is reported by the panic hook.
+            Err(mpsc::RecvTimeoutError::Disconnected) => Ok(()),
+        }
+    }
+
+    fn show_notification(&self, records: &[BufferedRecord]) -> DeliveryResult {
+        match &self.worker {
+            Some(worker) => {
+                worker.show(records.to_vec());
+                Ok(())
+            }
+            None => self.deliver(records),
+        }
+    }
+
+    /// Show the `records` within the rate and content limits.
+    fn deliver(&self, records: &[BufferedRecord]) -> DeliveryResult {
+        let admitted;
+        let records = match &self.rate_limiter {
+            Some(rate_limiter) => {
+                admitted = rate_limiter.lock().ignore_poison().admit(records);
+                if admitted.is_empty() {
+                    return Ok(());
+                }
+                admitted.as_slice()
+            }
+            None => records,
+        };
+        self.show_limited(records, Self::show_or_retry)
+    }
+
+    /// Show the `records` by the `show` within the content limits.
+    fn show_limited<F>(&self, records: &[BufferedRecord], show: F) -> DeliveryResult
+    where
+        F: Fn(&Self, &[BufferedRecord]) -> Result<()>,
+    {
+        let config = self.config();
+        if config.content_limits.is_unlimited() {
+            return show(self, records)
+                .map_err(|error| DeliveryError::new(error, records.to_vec()));
+        }
+        let mut notifications = config.content_limits.apply(records).into_iter();
+        while let Some(records) = notifications.next() {
+            if let Err(error) = show(self, &records) {
+                // Include the records not shown yet.
+                let records = records.into_iter().chain(notifications.flatten()).collect();
+                return Err(DeliveryError::new(error, records));
+            }
+        }
+        Ok(())
+    }
+
+    /// Show the `records`, or queue them to the [`Retrier`]
+    /// if the error is transient.
+    fn show_or_retry(&self, records: &[BufferedRecord]) -> Result<()> {
+        let result = self.show_records(records);
+        if let (Err(error), Some(retrier)) = (&result, &self.retrier)
+            && retrier.is_retryable(error)
+        {
+            retrier.retry(records.to_vec());
+            return Ok(());
+        }
+        result
+    }
+
+    fn show_records(&self, records: &[BufferedRecord]) -> Result<()> {
+        // This may be on the worker or other threads of the logger.
+        let _guard = ReentrancyGuard::enter();
+        let result = self.create_and_show(records);
+        match &result {
+            Ok(()) => self.stats.shown(records),
+            Err(error) => self.stats.error(error),
+        }
+        result
+    }
+
+    fn create_and_show(&self, records: &[BufferedRecord]) -> Result<()> {
+        let config = self.config();
+        let mut notification = (config.create_notification)(records)?;
+        if let Some(duration) = config.expiration_for(records) {
+            notification.expires_in(duration)?;
+        }
+        if !config.shows_popup(records) {
+            notification.suppress_popup(true)?;
+        }
+        let events = config.events.with_records(records);
+        self.notifier().show(&notification, &events)
+    }
+}
+
+impl log::Log for ToastLoggerInner {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        self.config().enabled(metadata)
+    }
+
+    fn log(&self, record: &log::Record) {
+        let Some(_guard) = ReentrancyGuard::enter() else {
+            // Logged from a callback of the logger.
+            // Showing it would recurse, or deadlock on the locks.
+            self.stats.reentrant();
+            eprintln!("{}: {}", record.level(), record.args());
+            return;
+        };
+        if let Err(error) = self.log_result(record) {
+            self.report(error);
+        }
     }
 
     fn flush(&self) {
         if let Err(error) = self.flush_result() {
-            eprintln!("Error flushing: {error}");
+            self.report(error);
+        }
+    }
+}
+
+/// Flushes the [`ToastLogger`] when dropped.
+///
+/// Please see [`ToastLoggerBuilder::init_with_guard()`]
+/// and [`ToastLoggerHandle::flush_guard()`].
+#[must_use = "the logs are flushed when the guard is dropped"]
+pub struct FlushGuard {
+    inner: Arc<ToastLoggerInner>,
+    timeout: Option<Duration>,
+}
+
+impl fmt::Debug for FlushGuard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FlushGuard")
+            .field("timeout", &self.timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+impl FlushGuard {
+    /// Set the maximum time to wait for the notifications to be shown
+    /// when the guard is dropped.
+    /// The default is the [`ToastLoggerBuilder::flush_timeout()`].
+    pub fn timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.timeout = Some(timeout);
+        self
+    }
+}
+
+impl Drop for FlushGuard {
+    fn drop(&mut self) {
+        let timeout = self.timeout.unwrap_or(self.inner.config().flush_timeout);
+        // Flush on another thread, so that the `timeout` applies
+        // without the background delivery.
+        if let Err(error) = self.inner.flush_on_thread(timeout) {
+            self.inner.report(error);
+        }
+    }
+}
+
+/// A handle to change the configurations of the [`ToastLogger`]
+/// after it's installed.
+///
+/// Each change is applied atomically.
+/// If the logger is installed by [`ToastLoggerBuilder::init()`],
+/// [`log::max_level()`] is kept consistent with the filters.
+/// # Examples
+/// ```no_run
+/// # use toast_logger_win::{Result, ToastLogger};
+/// # fn test() -> Result<()> {
+/// let handle = ToastLogger::builder().init()?;
+/// // When the user changes the verbosity in the settings dialog:
+/// handle.set_max_level(log::LevelFilter::Info);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct ToastLoggerHandle {
+    inner: Arc<ToastLoggerInner>,
+}
+
+impl ToastLoggerHandle {
+    /// Returns a [`FlushGuard`]
+    /// that flushes the logs when it's dropped.
+    /// Please see [`ToastLoggerBuilder::init_with_guard()`].
+    /// # Examples
+    /// ```no_run
+    /// # use toast_logger_win::{Result, ToastLogger};
+    /// fn main() -> Result<()> {
+    ///     let handle = ToastLogger::builder().auto_flush(false).init()?;
+    ///     let _guard = handle.flush_guard();
+    ///     handle.set_max_level(log::LevelFilter::Warn);
+    ///     log::warn!("This is shown even if `main` returns early.");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn flush_guard(&self) -> FlushGuard {
+        FlushGuard {
+            inner: self.inner.clone(),
+            timeout: None,
         }
     }
+
+    /// Set the maximum level of logs to be displayed.
+    /// Please see [`ToastLoggerBuilder::max_level()`].
+    pub fn set_max_level(&self, level: log::LevelFilter) {
+        self.inner
+            .reconfigure(|config| config.filter.set_default_level(level));
+    }
+
+    /// Set the maximum level of logs for the `target`.
+    /// Please see [`ToastLoggerBuilder::filter_target()`].
+    pub fn set_filter_target(&self, target: &str, level: log::LevelFilter) {
+        self.inner
+            .reconfigure(|config| config.filter.set_target_level(target, level));
+    }
+
+    /// Replace the maximum level and all the filter directives
+    /// with the `filters`.
+    /// Please see [`ToastLoggerBuilder::parse_filters()`] for the syntax.
+    ///
+    /// The configurations are not changed if the `filters` are invalid.
+    pub fn set_filters(&self, filters: &str) -> Result<()> {
+        let filter: Filter = filters.parse()?;
+        self.inner.reconfigure(|config| config.filter = filter);
+        Ok(())
+    }
+
+    /// Set whether to show logs immediately.
+    /// Please see [`ToastLoggerBuilder::auto_flush()`].
+    ///
+    /// Logs already buffered are kept until the next flush.
+    pub fn set_auto_flush(&self, is_auto_flush: bool) {
+        self.inner
+            .reconfigure(|config| config.is_auto_flush = is_auto_flush);
+    }
+
+    /// Set the function to format logs.
+    /// Please see [`ToastLoggerBuilder::format()`].
+    pub fn set_format<F>(&self, formatter: F)
+    where
+        F: Fn(&mut dyn fmt::Write, &log::Record) -> fmt::Result + Send + Sync + 'static,
+    {
+        self.inner
+            .reconfigure(|config| config.formatter = Arc::new(formatter));
+    }
+
+    /// Set the function to create notifications.
+    /// Please see [`ToastLoggerBuilder::create_notification()`].
+    pub fn set_create_notification<F>(&self, create: F)
+    where
+        F: Fn(&[BufferedRecord]) -> Result<Notification> + Send + Sync + 'static,
+    {
+        self.inner
+            .reconfigure(|config| config.create_notification = Arc::new(create));
+    }
+
+    /// Set the application ID for the Toast Notification.
+    /// Please see [`ToastLoggerBuilder::application_id()`].
+    ///
+    /// The configurations are not changed if creating the notifier fails.
+    pub fn set_application_id(&self, application_id: &str) -> Result<()> {
+        let notifier = Notifier::new_with_application_id(application_id)?;
+        self.inner.reconfigure(|config| {
+            config.application_id = application_id.into();
+            *self.inner.notifier.write().ignore_poison() = Arc::new(notifier);
+        });
+        Ok(())
+    }
+}
+
+impl log::Log for ToastLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &log::Record) {
+        self.inner.log(record);
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::{sync::Arc, time::SystemTime};
+
     use super::*;
+    use crate::mock::MockNotifier;
+
+    /// Serializes the tests that use [`log::max_level()`].
+    static LOG_GLOBALS: Mutex<()> = Mutex::new(());
+
+    /// Build a logger with a [`MockNotifier`].
+    fn mock_logger(builder: &mut ToastLoggerBuilder) -> (ToastLogger, MockNotifier) {
+        let notifier = MockNotifier::default();
+        let logger =
+            ToastLogger::new_with_notifier(builder.build_config(), Box::new(notifier.clone()))
+                .unwrap();
+        (logger, notifier)
+    }
+
+    fn log(logger: &ToastLogger, level: log::Level, target: &str, message: &str) {
+        logger.log(
+            &log::Record::builder()
+                .level(level)
+                .target(target)
+                .args(format_args!("{message}"))
+                .build(),
+        );
+    }
 
     #[test]
     fn builder_default() {
         let builder = ToastLogger::builder();
-        assert_eq!(builder.config.max_level, log::LevelFilter::Error);
+        assert_eq!(builder.config.filter.max_level(), log::LevelFilter::Error);
+        assert_eq!(builder.config.popup_level, log::LevelFilter::Trace);
         assert!(builder.config.is_auto_flush);
         assert_eq!(
             builder.config.application_id,
@@ -340,14 +2065,11 @@ mod tests {
             .args(format_args!("test"))
             .build();
         logger.log(&debug);
-        assert_eq!(logger.take_records(), None);
+        assert!(logger.inner.take_records().is_empty());
         logger.log(&info);
         assert_eq!(
-            logger.take_records().unwrap_or_default(),
-            [BufferedRecord {
-                level: log::Level::Info,
-                args: "INFO: test".into()
-            }]
+            logger.inner.take_records(),
+            [BufferedRecord::new(log::Level::Info, "", "INFO: test")]
         );
         Ok(())
     }
@@ -365,12 +2087,741 @@ mod tests {
             .build();
         logger.log(&info);
         assert_eq!(
-            logger.take_records().unwrap_or_default(),
-            [BufferedRecord {
-                level: log::Level::Info,
-                args: "test".into()
-            }]
+            logger.inner.take_records(),
+            [BufferedRecord::new(log::Level::Info, "", "test")]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn default_expiration() {
+        let mut builder = ToastLogger::builder();
+        builder.default_expiration(log::Level::Info, Duration::from_secs(60));
+        let record = |level| BufferedRecord::new(level, "", "");
+        let config = &builder.config;
+        assert_eq!(config.expiration_for(&[]), None);
+        assert_eq!(
+            config.expiration_for(&[record(log::Level::Info)]),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(config.expiration_for(&[record(log::Level::Warn)]), None);
+        assert_eq!(
+            config.expiration_for(&[record(log::Level::Info), record(log::Level::Error)]),
+            None
+        );
+
+        builder.default_expiration(log::Level::Info, Duration::ZERO);
+        assert_eq!(
+            builder.config.expiration_for(&[record(log::Level::Info)]),
+            None
+        );
+    }
+
+    #[test]
+    fn popup_level() {
+        let mut builder = ToastLogger::builder();
+        builder.popup_level(log::LevelFilter::Warn);
+        let record = |level| BufferedRecord::new(level, "", "");
+        let config = &builder.config;
+        assert!(config.shows_popup(&[]));
+        assert!(config.shows_popup(&[record(log::Level::Warn)]));
+        assert!(!config.shows_popup(&[record(log::Level::Info)]));
+        assert!(config.shows_popup(&[record(log::Level::Info), record(log::Level::Error)]));
+    }
+
+    #[test]
+    fn schedule() -> Result<()> {
+        let (logger, notifier) = mock_logger(&mut ToastLogger::builder());
+        let notification = Notification::new_with_text("test")?;
+        let delivery_time = SystemTime::now() + Duration::from_secs(60);
+        let interval = Duration::from_secs(5 * 60);
+        let mut schedule = Schedule::new(delivery_time);
+        schedule.tag("retry").snooze(interval, 3);
+        logger.schedule(&notification, &schedule)?;
+        schedule
+            .tag("other")
+            .snooze(Schedule::MAX_SNOOZE_INTERVAL, Schedule::MAX_SNOOZE_COUNT);
+        logger.schedule(&notification, &schedule)?;
+        let snoozes: Vec<_> = notifier
+            .schedules()
+            .iter()
+            .map(|schedule| schedule.snooze)
+            .collect();
+        assert_eq!(
+            snoozes,
+            [
+                Some((interval, 3)),
+                Some((Schedule::MAX_SNOOZE_INTERVAL, Schedule::MAX_SNOOZE_COUNT)),
+            ]
+        );
+        assert_eq!(
+            logger.scheduled()?,
+            [
+                ScheduledEntry {
+                    tag: "retry".into(),
+                    group: String::new(),
+                    delivery_time,
+                },
+                ScheduledEntry {
+                    tag: "other".into(),
+                    group: String::new(),
+                    delivery_time,
+                },
+            ]
+        );
+
+        assert_eq!(logger.cancel_scheduled("retry")?, 1);
+        assert_eq!(logger.scheduled()?.len(), 1);
+
+        // Invalid schedules are not passed to the notifier.
+        let second = Duration::from_secs(1);
+        let past = SystemTime::now() - second;
+        let min = Schedule::MIN_SNOOZE_INTERVAL;
+        let max = Schedule::MAX_SNOOZE_INTERVAL;
+        for (schedule, expected) in [
+            (Schedule::new(past), "the delivery time is in the past"),
+            (
+                Schedule::new(delivery_time).snooze(min - second, 1).clone(),
+                "the snooze interval is out of range",
+            ),
+            (
+                Schedule::new(delivery_time).snooze(max + second, 1).clone(),
+                "the snooze interval is out of range",
+            ),
+            (
+                Schedule::new(delivery_time).snooze(min, 0).clone(),
+                "the snooze count is out of range",
+            ),
+            (
+                Schedule::new(delivery_time)
+                    .snooze(min, Schedule::MAX_SNOOZE_COUNT + 1)
+                    .clone(),
+                "the snooze count is out of range",
+            ),
+        ] {
+            match logger.schedule(&notification, &schedule) {
+                Err(Error::InvalidSchedule(message)) => assert_eq!(message, expected),
+                result => panic!("{schedule:?}: {result:?}"),
+            }
+        }
+        assert_eq!(logger.scheduled()?.len(), 1);
+        assert_eq!(notifier.shown(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn history() -> Result<()> {
+        let (logger, notifier) = mock_logger(&mut ToastLogger::builder());
+        notifier.add_history("a", "");
+        notifier.add_history("a", "net");
+        notifier.add_history("b", "net");
+        notifier.add_history("c", "");
+
+        logger.remove_shown("a", "")?;
+        assert_eq!(
+            logger.history()?,
+            [
+                HistoryEntry {
+                    tag: "a".into(),
+                    group: "net".into()
+                },
+                HistoryEntry {
+                    tag: "b".into(),
+                    group: "net".into()
+                },
+                HistoryEntry {
+                    tag: "c".into(),
+                    group: String::new()
+                },
+            ]
+        );
+
+        // An empty tag doesn't reach the notifier.
+        assert!(matches!(
+            logger.remove_shown("", "net"),
+            Err(Error::EmptyTag)
+        ));
+        assert_eq!(logger.history()?.len(), 3);
+
+        logger.remove_shown_group("net")?;
+        assert_eq!(logger.history()?.len(), 1);
+
+        logger.clear_shown()?;
+        assert!(logger.history()?.is_empty());
+        assert_eq!(
+            notifier.removals(),
+            [
+                HistoryRemoval::Tag {
+                    tag: "a".into(),
+                    group: String::new()
+                },
+                HistoryRemoval::Group("net".into()),
+                HistoryRemoval::All,
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn events() -> Result<()> {
+        let activated = Arc::new(Mutex::new(Vec::new()));
+        let dismissed = Arc::new(Mutex::new(Vec::new()));
+        let mut builder = ToastLogger::builder();
+        builder.max_level(log::LevelFilter::Info);
+        let activated_clone = activated.clone();
+        builder.on_activated(move |arguments, inputs, records| {
+            activated_clone.lock().unwrap().push((
+                arguments.to_string(),
+                inputs.get("reply").cloned(),
+                records.to_vec(),
+            ));
+        });
+        let dismissed_clone = dismissed.clone();
+        builder.on_dismissed(move |reason, records| {
+            dismissed_clone
+                .lock()
+                .unwrap()
+                .push((reason, records.len()));
+        });
+        let (logger, notifier) = mock_logger(&mut builder);
+        log(&logger, log::Level::Info, "", "test");
+        assert_eq!(notifier.shown(), 1);
+
+        let events = notifier.events(0);
+        let inputs = HashMap::from([("reply".to_string(), "ok".to_string())]);
+        events.activated("action=retry", &inputs);
+        events.dismissed(DismissalReason::UserCanceled);
+        events.failed(&Error::NotInitialized);
+        assert_eq!(
+            *activated.lock().unwrap(),
+            [(
+                "action=retry".to_string(),
+                Some("ok".to_string()),
+                vec![BufferedRecord::new(log::Level::Info, "", "INFO: test")]
+            )]
+        );
+        assert_eq!(
+            *dismissed.lock().unwrap(),
+            [(DismissalReason::UserCanceled, 1)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_filters() -> Result<()> {
+        let logger = ToastLogger::builder()
+            .parse_filters("warn,my_app::net=info/lost")?
+            .auto_flush(false)
+            .build()?;
+        for (target, message) in [
+            ("my_app::net", "info: connection lost"),
+            ("my_app::net", "info: connected"),
+            ("my_app::ui", "info: connection lost"),
+        ] {
+            log(&logger, log::Level::Info, target, message);
+        }
+        assert_eq!(
+            logger.inner.take_records(),
+            [BufferedRecord::new(
+                log::Level::Info,
+                "my_app::net",
+                "INFO: info: connection lost"
+            )]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_env() -> Result<()> {
+        let vars = HashMap::from([
+            ("TEST", "warn,my_app=info"),
+            ("TEST_AUTO_FLUSH", "false"),
+            ("TEST_EXPIRES", "60,error=3600"),
+        ]);
+        let var = |name: &str| vars.get(name).map(|value| value.to_string());
+        let mut builder = ToastLogger::builder();
+        builder.application_id("app").parse_env_with("TEST", var)?;
+        let config = &builder.config;
+        assert_eq!(config.filter.max_level(), log::LevelFilter::Info);
+        assert!(!config.is_auto_flush);
+        assert_eq!(config.application_id, "app");
+        assert_eq!(
+            config.expirations.get(&log::Level::Info),
+            Some(&Duration::from_secs(60))
+        );
+        assert_eq!(
+            config.expirations.get(&log::Level::Error),
+            Some(&Duration::from_secs(3600))
+        );
+
+        // Zero removes the expiration, as `default_expiration()` does.
+        let var = |name: &str| (name == "TEST_EXPIRES").then(|| "error=0".to_string());
+        builder.parse_env_with("TEST", var)?;
+        assert_eq!(
+            builder.config.expirations.get(&log::Level::Info),
+            Some(&Duration::from_secs(60))
+        );
+        assert_eq!(builder.config.expirations.get(&log::Level::Error), None);
+        let var = |name: &str| (name == "TEST_EXPIRES").then(|| "0".to_string());
+        builder.parse_env_with("TEST", var)?;
+        assert!(builder.config.expirations.is_empty());
+
+        for (name, value) in [
+            ("TEST_AUTO_FLUSH", "maybe"),
+            ("TEST_EXPIRES", "-1"),
+            ("TEST_EXPIRES", "error=soon"),
+            ("TEST_EXPIRES", "fatal=60"),
+        ] {
+            let var = |n: &str| (n == name).then(|| value.to_string());
+            assert!(matches!(
+                ToastLogger::builder().parse_env_with("TEST", var),
+                Err(Error::InvalidEnv(..))
+            ));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn filter() -> Result<()> {
+        let logger = ToastLogger::builder()
+            .max_level(log::LevelFilter::Info)
+            .auto_flush(false)
+            .filter(|metadata| metadata.target().starts_with("ui::"))
+            .filter_record(|record| record.line() == Some(1))
+            .build()?;
+        for (target, line) in [("ui::main", 1), ("ui::main", 2), ("net", 1)] {
+            logger.log(
+                &log::Record::builder()
+                    .level(log::Level::Info)
+                    .target(target)
+                    .line(Some(line))
+                    .args(format_args!("{target}:{line}"))
+                    .build(),
+            );
+        }
+        assert!(
+            logger.enabled(
+                &log::Metadata::builder()
+                    .target("ui::main")
+                    .level(log::Level::Info)
+                    .build()
+            )
+        );
+        assert!(
+            !logger.enabled(
+                &log::Metadata::builder()
+                    .target("net")
+                    .level(log::Level::Info)
+                    .build()
+            )
+        );
+        assert_eq!(
+            logger.inner.take_records(),
+            [BufferedRecord::new(
+                log::Level::Info,
+                "ui::main",
+                "INFO: ui::main:1"
+            )]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn breadcrumbs() -> Result<()> {
+        let logger = ToastLogger::builder()
+            .max_level(log::LevelFilter::Info)
+            .auto_flush(false)
+            .breadcrumbs(log::Level::Error, 1)
+            .build()?;
+        for (level, message) in [
+            (log::Level::Info, "1"),
+            (log::Level::Info, "2"),
+            (log::Level::Error, "3"),
+        ] {
+            log(&logger, level, "", message);
+        }
+        assert_eq!(
+            logger.inner.take_records(),
+            [
+                BufferedRecord::new(log::Level::Info, "", "INFO: 2"),
+                BufferedRecord::new(log::Level::Error, "", "ERROR: 3"),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn batch() -> Result<()> {
+        let (logger, notifier) = mock_logger(
+            ToastLogger::builder()
+                .max_level(log::LevelFilter::Info)
+                .batch(Duration::from_secs(60 * 60), Duration::from_secs(60 * 60)),
+        );
+        for i in 0..10 {
+            log(&logger, log::Level::Error, "", &i.to_string());
+        }
+        // The timer is tested in the `batch` module.
+        // The periods are long enough not to fire during this test.
+        assert_eq!(notifier.shown(), 0);
+        assert!(logger.inner.flush_result().is_ok());
+        assert_eq!(notifier.shown(), 1);
+        assert!(logger.inner.take_records().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn rate_limit() -> Result<()> {
+        let (logger, notifier) = mock_logger(
+            ToastLogger::builder()
+                .max_level(log::LevelFilter::Info)
+                .rate_limit(2, Duration::from_secs(60 * 60)),
+        );
+        for i in 0..10 {
+            log(&logger, log::Level::Error, "", &i.to_string());
+        }
+        assert_eq!(notifier.shown(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn dedup() -> Result<()> {
+        let (logger, _) = mock_logger(
+            ToastLogger::builder()
+                .auto_flush(false)
+                .dedup(Duration::from_secs(60)),
+        );
+        for message in ["lost", "lost", "other", "lost"] {
+            log(&logger, log::Level::Error, "", message);
+        }
+        let records = logger.inner.take_records();
+        assert_eq!(
+            records,
+            [
+                BufferedRecord::new(log::Level::Error, "", "ERROR: lost"),
+                BufferedRecord::new(log::Level::Error, "", "ERROR: other"),
+            ]
+        );
+        assert_eq!(records[0].count, 3);
+        assert_eq!(records[1].count, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn dedup_auto_flush() -> Result<()> {
+        let shown = Arc::new(Mutex::new(Vec::new()));
+        let shown_clone = shown.clone();
+        let (logger, _) = mock_logger(
+            ToastLogger::builder()
+                .dedup(Duration::from_secs(60 * 60))
+                .create_notification(move |records| {
+                    shown_clone.lock().unwrap().extend_from_slice(records);
+                    Notification::new_with_records(records)
+                }),
+        );
+        for message in ["lost", "lost", "lost", "other"] {
+            log(&logger, log::Level::Error, "", message);
+        }
+        assert_eq!(shown.lock().unwrap().len(), 2);
+
+        // The repetitions are shown on flush.
+        logger.flush()?;
+        let shown = shown.lock().unwrap();
+        assert_eq!(shown.len(), 3);
+        assert_eq!(shown[2].args, "ERROR: lost");
+        assert_eq!(shown[2].count, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn buffer_capacity() -> Result<()> {
+        let (logger, notifier) = mock_logger(
+            ToastLogger::builder()
+                .auto_flush(false)
+                .buffer_capacity(2)
+                .overflow_policy(OverflowPolicy::Flush),
+        );
+        for i in 0..5 {
+            log(&logger, log::Level::Error, "", &i.to_string());
+        }
+        assert_eq!(notifier.shown(), 2);
+        assert_eq!(
+            logger.inner.take_records(),
+            [BufferedRecord::new(log::Level::Error, "", "ERROR: 4")]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn content_limits() -> Result<()> {
+        let (logger, notifier) = mock_logger(
+            ToastLogger::builder()
+                .auto_flush(false)
+                .max_records(2)
+                .split_notifications(true),
+        );
+        for i in 0..5 {
+            log(&logger, log::Level::Error, "", &i.to_string());
+        }
+        assert!(logger.inner.flush_result().is_ok());
+        assert_eq!(notifier.shown(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn background_delivery() -> Result<()> {
+        let (logger, notifier) = mock_logger(ToastLogger::builder().background_delivery(true));
+        for i in 0..3 {
+            log(&logger, log::Level::Error, "", &i.to_string());
+        }
+        assert!(logger.inner.flush_result().is_ok());
+        assert_eq!(notifier.shown(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn show_panic() -> Result<()> {
+        let (logger, notifier) = mock_logger(ToastLogger::builder().auto_flush(false));
+        log(&logger, log::Level::Error, "", "before");
+        assert!(
+            logger
+                .inner
+                .show_panic("boom", Some(Location::caller()))
+                .is_ok()
+        );
+        assert_eq!(notifier.shown(), 1);
+        assert!(logger.inner.take_records().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn show_panic_rate_limit() {
+        let (logger, notifier) =
+            mock_logger(ToastLogger::builder().rate_limit(1, Duration::from_secs(60 * 60)));
+        log(&logger, log::Level::Error, "", "before");
+        log(&logger, log::Level::Error, "", "suppressed");
+        assert_eq!(notifier.shown(), 1);
+        // Panics are not suppressed by the rate limits.
+        assert!(logger.inner.show_panic("boom", None).is_ok());
+        assert_eq!(notifier.shown(), 2);
+    }
+
+    #[test]
+    fn handle() -> Result<()> {
+        let (logger, _) = mock_logger(ToastLogger::builder().auto_flush(false));
+        let handle = logger.handle();
+        let info = log::Metadata::builder().level(log::Level::Info).build();
+        assert!(!logger.enabled(&info));
+        handle.set_max_level(log::LevelFilter::Info);
+        assert!(logger.enabled(&info));
+
+        assert!(handle.set_filters("info=loud").is_err());
+        assert!(logger.enabled(&info));
+        handle.set_filters("warn")?;
+        assert!(!logger.enabled(&info));
+
+        handle.set_format(|buf, record| write!(buf, "{}", record.args()));
+        log(&logger, log::Level::Warn, "", "test");
+        assert_eq!(
+            logger.inner.take_records(),
+            [BufferedRecord::new(log::Level::Warn, "", "test")]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn handle_not_installed() {
+        let _lock = LOG_GLOBALS.lock().unwrap();
+        let max_level = log::max_level();
+        let (logger, _) = mock_logger(&mut ToastLogger::builder());
+        let handle = logger.handle();
+        handle.set_max_level(log::LevelFilter::Trace);
+        handle.set_filter_target("target", log::LevelFilter::Debug);
+        assert!(handle.set_filters("trace").is_ok());
+        assert_eq!(log::max_level(), max_level);
+    }
+
+    #[test]
+    fn flush_guard() {
+        let (logger, notifier) = mock_logger(ToastLogger::builder().auto_flush(false));
+        let guard = logger.handle().flush_guard();
+        log(&logger, log::Level::Error, "", "test");
+        assert_eq!(notifier.shown(), 0);
+        drop(guard);
+        assert_eq!(notifier.shown(), 1);
+
+        let (logger, _) = mock_logger(
+            ToastLogger::builder()
+                .auto_flush(false)
+                .on_error(|_, _| {})
+                .create_notification(|records| {
+                    thread::sleep(Duration::from_millis(500));
+                    Notification::new_with_records(records)
+                }),
+        );
+        let mut guard = logger.handle().flush_guard();
+        guard.timeout(Duration::from_millis(10));
+        log(&logger, log::Level::Error, "", "test");
+        let start = Instant::now();
+        drop(guard);
+        assert!(start.elapsed() < Duration::from_millis(400));
+    }
+
+    #[test]
+    fn flush_on_thread() {
+        let (logger, _) = mock_logger(
+            ToastLogger::builder()
+                .auto_flush(false)
+                .create_notification(|records| {
+                    thread::sleep(Duration::from_millis(500));
+                    Notification::new_with_records(records)
+                }),
+        );
+        log(&logger, log::Level::Error, "", "test");
+        assert!(matches!(
+            logger.inner.flush_on_thread(Duration::from_millis(10)),
+            Err(DeliveryError {
+                error: Error::FlushTimeout(_),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn try_init() -> Result<()> {
+        // Other tests don't install the logger.
+        let _lock = LOG_GLOBALS.lock().unwrap();
+        let mut results: Vec<_> = (0..4)
+            .map(|_| {
+                thread::spawn(|| {
+                    ToastLogger::builder()
+                        .max_level(log::LevelFilter::Info)
+                        .try_init()
+                })
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|thread| thread.join().unwrap())
+            .collect();
+        // Only one of the racing threads installs the logger.
+        let index = results.iter().position(|result| result.is_ok()).unwrap();
+        let handle = results.swap_remove(index)?;
+        assert!(
+            results
+                .iter()
+                .all(|result| matches!(result, Err(Error::AlreadyInitialized)))
+        );
+        assert_eq!(log::max_level(), log::LevelFilter::Info);
+        assert!(ToastLogger::instance()?.inner.is_installed());
+        assert!(matches!(
+            ToastLogger::builder().try_init(),
+            Err(Error::AlreadyInitialized)
+        ));
+
+        handle.set_max_level(log::LevelFilter::Warn);
+        assert_eq!(log::max_level(), log::LevelFilter::Warn);
+        Ok(())
+    }
+
+    #[test]
+    fn on_error() -> Result<()> {
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let errors_clone = errors.clone();
+        let (logger, _) = mock_logger(
+            ToastLogger::builder()
+                .create_notification(|_| Err(Error::ExpirationInPast))
+                .on_error(move |error, records| {
+                    errors_clone
+                        .lock()
+                        .unwrap()
+                        .push((error.to_string(), records.to_vec()));
+                }),
+        );
+        log(&logger, log::Level::Error, "", "test");
+        assert_eq!(
+            *errors.lock().unwrap(),
+            [(
+                Error::ExpirationInPast.to_string(),
+                vec![BufferedRecord::new(log::Level::Error, "", "ERROR: test")]
+            )]
         );
         Ok(())
     }
+
+    #[test]
+    fn stats() -> Result<()> {
+        let (logger, _) = mock_logger(
+            ToastLogger::builder()
+                .auto_flush(false)
+                .filter_target("noisy", log::LevelFilter::Off)
+                .buffer_capacity(2)
+                .dedup(Duration::from_secs(60)),
+        );
+        for (target, message) in [
+            ("", "1"),
+            ("noisy", "2"),
+            ("", "1"),
+            ("", "3"),
+            ("", "4"),
+            ("", "4"),
+        ] {
+            log(&logger, log::Level::Error, target, message);
+        }
+        assert!(logger.inner.flush_result().is_ok());
+        let stats = logger.inner.stats();
+        assert_eq!(stats.records_seen, 6);
+        assert_eq!(stats.records_filtered, 1);
+        assert_eq!(stats.records_buffered, 5);
+        // The "1" repeated twice is dropped, and counted only as dropped.
+        assert_eq!(stats.records_dropped, 2);
+        // "3" and "4", without the number of the dropped records.
+        assert_eq!(stats.records_shown, 2);
+        // The repeated "4".
+        assert_eq!(stats.records_deduplicated, 1);
+        // Each record is counted once.
+        assert_eq!(
+            stats.records_filtered
+                + stats.records_dropped
+                + stats.records_shown
+                + stats.records_deduplicated,
+            stats.records_seen
+        );
+        assert_eq!(stats.notifications_shown, 1);
+        assert!(stats.last_delivery.is_some());
+        assert_eq!(stats.last_error, None);
+        Ok(())
+    }
+
+    #[test]
+    fn instances() -> Result<()> {
+        let (loggers, notifiers): (Vec<_>, Vec<_>) = (0..2)
+            .map(|_| mock_logger(ToastLogger::builder().auto_flush(false)))
+            .unzip();
+        log(&loggers[0].clone(), log::Level::Error, "", "test");
+        loggers[1].flush()?;
+        assert_eq!(notifiers[0].shown(), 0);
+        loggers[0].flush()?;
+        assert_eq!(notifiers[0].shown(), 1);
+        assert_eq!(notifiers[1].shown(), 0);
+        assert_eq!(loggers[0].stats().notifications_shown, 1);
+        assert_eq!(loggers[1].stats(), Stats::default());
+        Ok(())
+    }
+
+    #[test]
+    fn reentrancy() -> Result<()> {
+        let this = Arc::new(OnceLock::<ToastLogger>::new());
+        let this_clone = this.clone();
+        let (logger, notifier) =
+            mock_logger(ToastLogger::builder().create_notification(move |records| {
+                if let Some(logger) = this_clone.get() {
+                    log(logger, log::Level::Error, "", "nested");
+                }
+                Notification::new_with_records(records)
+            }));
+        let _ = this.set(logger.clone());
+        log(&logger, log::Level::Error, "", "test");
+        assert_eq!(notifier.shown(), 1);
+        assert_eq!(logger.stats().records_reentrant, 1);
+        Ok(())
+    }
 }
@@ -0,0 +1,118 @@
+//!
+//! The backend for other platforms than Windows.
+//!
+//! Notifications can be created, so that the logic can be tested,
+//! but showing them returns [`Error::UnsupportedPlatform`].
+//! The same interfaces as the `win` module are provided.
+//!
+
+use std::time::SystemTime;
+
+use crate::{
+    Error, HistoryEntry, HistoryRemoval, NotificationMirroring, Result, Schedule, ScheduledEntry,
+    events::NotificationEvents,
+};
+
+/// The properties of a notification.
+///
+/// They are read only by tests.
+#[allow(dead_code)]
+#[derive(Debug, Default)]
+pub struct NotificationImpl {
+    pub(crate) text: String,
+    pub(crate) expiration_time: Option<SystemTime>,
+    pub(crate) is_expires_on_reboot: bool,
+    pub(crate) tag: String,
+    pub(crate) group: String,
+    pub(crate) is_suppress_popup: bool,
+    pub(crate) mirroring: NotificationMirroring,
+    pub(crate) remote_id: String,
+}
+
+impl NotificationImpl {
+    pub fn new_with_text(text: &str) -> Result<Self> {
+        Ok(Self {
+            text: text.into(),
+            ..Default::default()
+        })
+    }
+
+    pub fn expires_at(&mut self, time: SystemTime) -> Result<()> {
+        self.expiration_time = Some(time);
+        Ok(())
+    }
+
+    pub fn expires_on_reboot(&mut self, value: bool) -> Result<()> {
+        self.is_expires_on_reboot = value;
+        Ok(())
+    }
+
+    pub fn tag(&mut self, value: &str) -> Result<()> {
+        self.tag = value.into();
+        Ok(())
+    }
+
+    pub fn group(&mut self, value: &str) -> Result<()> {
+        self.group = value.into();
+        Ok(())
+    }
+
+    pub fn suppress_popup(&mut self, value: bool) -> Result<()> {
+        self.is_suppress_popup = value;
+        Ok(())
+    }
+
+    pub fn notification_mirroring(&mut self, value: NotificationMirroring) -> Result<()> {
+        self.mirroring = value;
+        Ok(())
+    }
+
+    pub fn remote_id(&mut self, value: &str) -> Result<()> {
+        self.remote_id = value.into();
+        Ok(())
+    }
+}
+
+/// A notifier that fails all operations.
+#[derive(Debug)]
+pub struct NotifierImpl {
+    _private: (),
+}
+
+impl NotifierImpl {
+    pub fn new_with_application_id(_application_id: &str) -> Result<Self> {
+        Ok(Self { _private: () })
+    }
+
+    pub fn show_with_events(
+        &self,
+        _notification: &NotificationImpl,
+        _events: &NotificationEvents,
+    ) -> Result<()> {
+        Err(Error::UnsupportedPlatform)
+    }
+
+    pub fn add_to_schedule(
+        &self,
+        _notification: &NotificationImpl,
+        _schedule: &Schedule,
+    ) -> Result<()> {
+        Err(Error::UnsupportedPlatform)
+    }
+
+    pub fn scheduled(&self) -> Result<Vec<ScheduledEntry>> {
+        Err(Error::UnsupportedPlatform)
+    }
+
+    pub fn remove_from_schedule(&self, _tag: &str) -> Result<usize> {
+        Err(Error::UnsupportedPlatform)
+    }
+
+    pub fn history(&self) -> Result<Vec<HistoryEntry>> {
+        Err(Error::UnsupportedPlatform)
+    }
+
+    pub fn remove_history(&self, _removal: &HistoryRemoval) -> Result<()> {
+        Err(Error::UnsupportedPlatform)
+    }
+}
@@ -2,18 +2,83 @@
 //! Thin wrappers for the Windows APIs.
 //!
 
-use std::time::Duration;
+use std::{
+    collections::HashMap,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
 use windows::{
-    Foundation::{DateTime, IReference, PropertyValue},
-    Globalization::Calendar,
+    Foundation::{
+        DateTime, IPropertyValue, IReference, PropertyValue, TimeSpan, TypedEventHandler,
+    },
     UI::Notifications::{
-        ToastNotification, ToastNotificationManager, ToastNotifier, ToastTemplateType,
+        self, ScheduledToastNotification, ToastActivatedEventArgs, ToastDismissalReason,
+        ToastDismissedEventArgs, ToastFailedEventArgs, ToastNotification, ToastNotificationHistory,
+        ToastNotificationManager, ToastNotifier, ToastTemplateType,
     },
-    core::{IInspectable, Interface},
+    core::{HSTRING, IInspectable, Interface},
+};
+
+use crate::{
+    DismissalReason, Error, HistoryEntry, HistoryRemoval, NotificationMirroring, Result, Schedule,
+    ScheduledEntry, events::NotificationEvents,
 };
 
-use crate::Result;
+/// The number of 100-nanosecond intervals
+/// from 1601-01-01 (the Windows epoch) to 1970-01-01 (the Unix epoch).
+const UNIX_EPOCH_IN_TICKS: i128 = 116_444_736_000_000_000;
+
+/// Convert a [`SystemTime`] to a [`DateTime`].
+fn to_date_time(time: SystemTime) -> Result<DateTime> {
+    let nanos: i128 = match time.duration_since(UNIX_EPOCH) {
+        Ok(duration) => duration.as_nanos() as i128,
+        Err(error) => -(error.duration().as_nanos() as i128),
+    };
+    let ticks = nanos / 100 + UNIX_EPOCH_IN_TICKS;
+    let universal_time = i64::try_from(ticks).map_err(|_| Error::ExpirationOutOfRange)?;
+    Ok(DateTime {
+        UniversalTime: universal_time,
+    })
+}
+
+/// Convert a [`DateTime`] to a [`SystemTime`].
+fn from_date_time(dt: DateTime) -> SystemTime {
+    let ticks = dt.UniversalTime as i128 - UNIX_EPOCH_IN_TICKS;
+    let abs_ticks = ticks.unsigned_abs();
+    let duration = Duration::new(
+        (abs_ticks / 10_000_000) as u64,
+        (abs_ticks % 10_000_000 * 100) as u32,
+    );
+    if ticks >= 0 {
+        UNIX_EPOCH + duration
+    } else {
+        UNIX_EPOCH - duration
+    }
+}
+
+/// Convert a [`Duration`] to a [`TimeSpan`].
+fn to_time_span(duration: Duration) -> Result<TimeSpan> {
+    let ticks = i64::try_from(duration.as_nanos() / 100)
+        .map_err(|_| Error::InvalidSchedule("the duration is out of range"))?;
+    Ok(TimeSpan { Duration: ticks })
+}
+
+/// The `HRESULT`s that may succeed when retried,
+/// such as while the shell is restarting.
+const TRANSIENT_ERRORS: [u32; 7] = [
+    0x8000_000A, // E_PENDING
+    0x8001_0001, // RPC_E_CALL_REJECTED
+    0x8001_0108, // RPC_E_DISCONNECTED
+    0x8001_010A, // RPC_E_SERVERCALL_RETRYLATER
+    0x8007_06BA, // RPC_S_SERVER_UNAVAILABLE
+    0x8007_06BE, // RPC_S_CALL_FAILED
+    0x8008_0005, // CO_E_SERVER_EXEC_FAILURE
+];
+
+/// Whether the `error` may succeed when retried.
+pub(crate) fn is_transient(error: &windows::core::Error) -> bool {
+    TRANSIENT_ERRORS.contains(&(error.code().0 as u32))
+}
 
 /// Represents a Toast Notification.
 ///
@@ -47,16 +112,49 @@ impl NotificationImpl {
         Ok(Self { notification })
     }
 
-    /// Set the expiration time to the `duration` from the current time.
-    pub fn expires_in(&mut self, duration: Duration) -> Result<()> {
-        let win_cal = Calendar::new()?;
-        win_cal.AddSeconds(duration.as_secs() as i32)?;
-        let dt = win_cal.GetDateTime()?;
+    /// Set the expiration time to the `time`.
+    pub fn expires_at(&mut self, time: SystemTime) -> Result<()> {
+        let dt = to_date_time(time)?;
         let dt_obj: IInspectable = PropertyValue::CreateDateTime(dt)?;
         let dt_ref: IReference<DateTime> = dt_obj.cast()?;
         self.notification.SetExpirationTime(&dt_ref)?;
         Ok(())
     }
+
+    /// Set whether the notification expires when the system reboots.
+    pub fn expires_on_reboot(&mut self, value: bool) -> Result<()> {
+        self.notification.SetExpiresOnReboot(value)?;
+        Ok(())
+    }
+
+    pub fn tag(&mut self, value: &str) -> Result<()> {
+        self.notification.SetTag(&value.into())?;
+        Ok(())
+    }
+
+    pub fn group(&mut self, value: &str) -> Result<()> {
+        self.notification.SetGroup(&value.into())?;
+        Ok(())
+    }
+
+    pub fn suppress_popup(&mut self, value: bool) -> Result<()> {
+        self.notification.SetSuppressPopup(value)?;
+        Ok(())
+    }
+
+    pub fn notification_mirroring(&mut self, value: NotificationMirroring) -> Result<()> {
+        let value = match value {
+            NotificationMirroring::Allowed => Notifications::NotificationMirroring::Allowed,
+            NotificationMirroring::Disabled => Notifications::NotificationMirroring::Disabled,
+        };
+        self.notification.SetNotificationMirroring(value)?;
+        Ok(())
+    }
+
+    pub fn remote_id(&mut self, value: &str) -> Result<()> {
+        self.notification.SetRemoteId(&value.into())?;
+        Ok(())
+    }
 }
 
 /// A thin wrapper for the [`windows::UI::Notifications::ToastNotifier`].
@@ -68,17 +166,196 @@ impl NotificationImpl {
 #[derive(Debug)]
 pub struct NotifierImpl {
     notifier: ToastNotifier,
+    application_id: HSTRING,
 }
 
 impl NotifierImpl {
     pub fn new_with_application_id(application_id: &str) -> Result<Self> {
+        let application_id: HSTRING = application_id.into();
+        let manager = ToastNotificationManager::GetDefault()?;
+        let notifier = manager.CreateToastNotifierWithId(&application_id)?;
+        Ok(Self {
+            notifier,
+            application_id,
+        })
+    }
+
+    /// Show the `notification` with the event handlers.
+    pub fn show_with_events(
+        &self,
+        notification: &NotificationImpl,
+        events: &NotificationEvents,
+    ) -> Result<()> {
+        let notification = &notification.notification;
+        if events.has_activated() {
+            let events = events.clone();
+            notification.Activated(&TypedEventHandler::<ToastNotification, IInspectable>::new(
+                move |_, args| {
+                    let args: ToastActivatedEventArgs = args.ok()?.cast()?;
+                    let arguments = args.Arguments()?.to_string_lossy();
+                    let mut inputs = HashMap::new();
+                    for input in &args.UserInput()? {
+                        let value = input.Value()?.cast::<IPropertyValue>()?.GetString()?;
+                        inputs.insert(input.Key()?.to_string_lossy(), value.to_string_lossy());
+                    }
+                    events.activated(&arguments, &inputs);
+                    Ok(())
+                },
+            ))?;
+        }
+        if events.has_dismissed() {
+            let events = events.clone();
+            notification.Dismissed(&TypedEventHandler::<
+                ToastNotification,
+                ToastDismissedEventArgs,
+            >::new(move |_, args| {
+                let reason = match args.ok()?.Reason()? {
+                    ToastDismissalReason::ApplicationHidden => DismissalReason::ApplicationHidden,
+                    ToastDismissalReason::TimedOut => DismissalReason::TimedOut,
+                    _ => DismissalReason::UserCanceled,
+                };
+                events.dismissed(reason);
+                Ok(())
+            }))?;
+        }
+        if events.has_failed() {
+            let events = events.clone();
+            notification.Failed(
+                &TypedEventHandler::<ToastNotification, ToastFailedEventArgs>::new(
+                    move |_, args| {
+                        let error = windows::core::Error::from_hresult(args.ok()?.ErrorCode()?);
+                        events.failed(&error.into());
+                        Ok(())
+                    },
+                ),
+            )?;
+        }
+        self.notifier.Show(notification)?;
+        Ok(())
+    }
+
+    /// Schedule the `notification` by creating a [`ScheduledToastNotification`].
+    pub fn add_to_schedule(
+        &self,
+        notification: &NotificationImpl,
+        schedule: &Schedule,
+    ) -> Result<()> {
+        let notification = &notification.notification;
+        let content = notification.Content()?;
+        let delivery_time = to_date_time(schedule.delivery_time)?;
+        let scheduled = match schedule.snooze {
+            Some((interval, max_count)) => {
+                ScheduledToastNotification::CreateScheduledToastNotificationRecurring(
+                    &content,
+                    delivery_time,
+                    to_time_span(interval)?,
+                    max_count,
+                )?
+            }
+            None => ScheduledToastNotification::CreateScheduledToastNotification(
+                &content,
+                delivery_time,
+            )?,
+        };
+        if !schedule.tag.is_empty() {
+            scheduled.SetTag(&schedule.tag.as_str().into())?;
+        }
+        if !schedule.group.is_empty() {
+            scheduled.SetGroup(&schedule.group.as_str().into())?;
+        }
+        scheduled.SetSuppressPopup(notification.SuppressPopup()?)?;
+        if let Ok(expiration_time) = notification.ExpirationTime() {
+            scheduled.SetExpirationTime(&expiration_time)?;
+        }
+        self.notifier.AddToSchedule(&scheduled)?;
+        Ok(())
+    }
+
+    /// List the pending [`ScheduledToastNotification`]s.
+    pub fn scheduled(&self) -> Result<Vec<ScheduledEntry>> {
+        let mut entries = Vec::new();
+        for scheduled in self.notifier.GetScheduledToastNotifications()? {
+            entries.push(ScheduledEntry {
+                tag: scheduled.Tag()?.to_string_lossy(),
+                group: scheduled.Group()?.to_string_lossy(),
+                delivery_time: from_date_time(scheduled.DeliveryTime()?),
+            });
+        }
+        Ok(entries)
+    }
+
+    /// Remove the pending [`ScheduledToastNotification`]s with the `tag`.
+    pub fn remove_from_schedule(&self, tag: &str) -> Result<usize> {
+        let mut count = 0;
+        for scheduled in self.notifier.GetScheduledToastNotifications()? {
+            if scheduled.Tag()? == tag {
+                self.notifier.RemoveFromSchedule(&scheduled)?;
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    /// The [`ToastNotificationHistory`] for the current user.
+    fn history_manager() -> Result<ToastNotificationHistory> {
         let manager = ToastNotificationManager::GetDefault()?;
-        let notifier = manager.CreateToastNotifierWithId(&application_id.into())?;
-        Ok(Self { notifier })
+        Ok(manager.History()?)
+    }
+
+    /// List the notifications of this application in the Action Center.
+    pub fn history(&self) -> Result<Vec<HistoryEntry>> {
+        let history = Self::history_manager()?;
+        let mut entries = Vec::new();
+        for notification in history.GetHistoryWithId(&self.application_id)? {
+            entries.push(HistoryEntry {
+                tag: notification.Tag()?.to_string_lossy(),
+                group: notification.Group()?.to_string_lossy(),
+            });
+        }
+        Ok(entries)
     }
 
-    pub fn show(&self, notification: &NotificationImpl) -> Result<()> {
-        self.notifier.Show(&notification.notification)?;
+    /// Remove the notifications of this application.
+    pub fn remove_history(&self, removal: &HistoryRemoval) -> Result<()> {
+        let history = Self::history_manager()?;
+        let application_id = &self.application_id;
+        match removal {
+            HistoryRemoval::Tag { tag, group } => history.RemoveGroupedTagWithId(
+                &tag.as_str().into(),
+                &group.as_str().into(),
+                application_id,
+            )?,
+            HistoryRemoval::Group(group) => {
+                history.RemoveGroupWithId(&group.as_str().into(), application_id)?
+            }
+            HistoryRemoval::All => history.ClearWithId(application_id)?,
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history() -> Result<()> {
+        let application_id = "toast-logger-win.test.history";
+        let notifier = NotifierImpl::new_with_application_id(application_id)?;
+        assert_eq!(notifier.application_id, application_id);
+
+        // Without the application ID, these fail in unpackaged processes.
+        notifier.remove_history(&HistoryRemoval::Tag {
+            tag: "tag".into(),
+            group: "group".into(),
+        })?;
+        notifier.remove_history(&HistoryRemoval::Tag {
+            tag: "tag".into(),
+            group: String::new(),
+        })?;
+        notifier.remove_history(&HistoryRemoval::Group("group".into()))?;
+        notifier.remove_history(&HistoryRemoval::All)?;
+        assert!(notifier.history()?.is_empty());
         Ok(())
     }
 }
@@ -0,0 +1,126 @@
+//!
+//! Extensions to the [`winrt-toast` crate]
+//! to provide the same interfaces as the `win` module.
+//!
+//! [`winrt-toast` crate]: https://docs.rs/winrt-toast/latest/winrt_toast/
+//!
+
+use std::time::SystemTime;
+
+use crate::{
+    Error, HistoryEntry, HistoryRemoval, NotificationMirroring, Result, Schedule, ScheduledEntry,
+    events::NotificationEvents,
+};
+
+/// The properties not provided by [`winrt_toast::Toast`].
+/// The tag, the group, and the remote ID are set by its builders.
+pub(crate) trait ToastExt {
+    fn expires_at(&mut self, time: SystemTime) -> Result<()>;
+    fn expires_on_reboot(&mut self, value: bool) -> Result<()>;
+    fn suppress_popup(&mut self, value: bool) -> Result<()>;
+    fn notification_mirroring(&mut self, value: NotificationMirroring) -> Result<()>;
+}
+
+impl ToastExt for winrt_toast::Toast {
+    fn expires_at(&mut self, time: SystemTime) -> Result<()> {
+        let duration = time
+            .duration_since(SystemTime::now())
+            .map_err(|_| Error::ExpirationInPast)?;
+        self.expires_in(duration);
+        Ok(())
+    }
+
+    fn expires_on_reboot(&mut self, _value: bool) -> Result<()> {
+        Err(Error::NotSupported("expires_on_reboot"))
+    }
+
+    fn suppress_popup(&mut self, _value: bool) -> Result<()> {
+        Err(Error::NotSupported("suppress_popup"))
+    }
+
+    fn notification_mirroring(&mut self, _value: NotificationMirroring) -> Result<()> {
+        Err(Error::NotSupported("notification_mirroring"))
+    }
+}
+
+pub(crate) trait ToastManagerExt {
+    fn show_with_events(
+        &self,
+        toast: &winrt_toast::Toast,
+        events: &NotificationEvents,
+    ) -> Result<()>;
+    fn add_to_schedule(&self, toast: &winrt_toast::Toast, schedule: &Schedule) -> Result<()>;
+    fn scheduled(&self) -> Result<Vec<ScheduledEntry>>;
+    fn remove_from_schedule(&self, tag: &str) -> Result<usize>;
+    fn history(&self) -> Result<Vec<HistoryEntry>>;
+    fn remove_history(&self, removal: &HistoryRemoval) -> Result<()>;
+}
+
+impl ToastManagerExt for winrt_toast::ToastManager {
+    fn show_with_events(
+        &self,
+        toast: &winrt_toast::Toast,
+        events: &NotificationEvents,
+    ) -> Result<()> {
+        if !events.is_empty() {
+            return Err(Error::NotSupported("on_activated"));
+        }
+        self.show(toast)?;
+        Ok(())
+    }
+
+    fn add_to_schedule(&self, _toast: &winrt_toast::Toast, _schedule: &Schedule) -> Result<()> {
+        Err(Error::NotSupported("schedule"))
+    }
+
+    fn scheduled(&self) -> Result<Vec<ScheduledEntry>> {
+        Err(Error::NotSupported("scheduled"))
+    }
+
+    fn remove_from_schedule(&self, _tag: &str) -> Result<usize> {
+        Err(Error::NotSupported("cancel_scheduled"))
+    }
+
+    fn history(&self) -> Result<Vec<HistoryEntry>> {
+        Err(Error::NotSupported("history"))
+    }
+
+    fn remove_history(&self, removal: &HistoryRemoval) -> Result<()> {
+        Err(Error::NotSupported(match removal {
+            HistoryRemoval::Tag { .. } => "remove_shown",
+            HistoryRemoval::Group(_) => "remove_shown_group",
+            HistoryRemoval::All => "clear_shown",
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history() {
+        let manager = winrt_toast::ToastManager::new("toast-logger-win.test.history");
+        assert!(matches!(
+            manager.history(),
+            Err(Error::NotSupported("history"))
+        ));
+        let removals = [
+            (
+                HistoryRemoval::Tag {
+                    tag: "tag".into(),
+                    group: "group".into(),
+                },
+                "remove_shown",
+            ),
+            (HistoryRemoval::Group("group".into()), "remove_shown_group"),
+            (HistoryRemoval::All, "clear_shown"),
+        ];
+        for (removal, name) in removals {
+            assert!(matches!(
+                manager.remove_history(&removal),
+                Err(Error::NotSupported(error)) if error == name
+            ));
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+    time::Duration,
+};
+
+use crate::{BufferedRecord, Error, Result};
+
+#[cfg(doc)]
+use crate::ToastLoggerBuilder;
+
+enum Job {
+    Show(Vec<BufferedRecord>),
+    Flush(Sender<()>),
+}
+
+/// A background thread that creates and shows notifications.
+///
+/// Please see [`ToastLoggerBuilder::background_delivery()`].
+pub(crate) struct Worker {
+    sender: Sender<Job>,
+}
+
+impl Worker {
+    /// Start the worker thread.
+    /// The `show` is called on the worker thread for each notification.
+    /// The thread stops when the `show` returns `false`,
+    /// or when the `Worker` is dropped.
+    /// It continues when the `show` panics,
+    /// so that the later notifications are still shown.
+    ///
+    /// Returns [`Error::SpawnThread`] if the thread can't be spawned.
+    pub fn new<F>(show: F) -> Result<Self>
+    where
+        F: Fn(&[BufferedRecord]) -> bool + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel();
+        thread::Builder::new()
+            .name("toast-logger-worker".into())
+            .spawn(move || Self::run(&receiver, show))
+            .map_err(Error::SpawnThread)?;
+        Ok(Self { sender })
+    }
+
+    /// Queue the `records` to show in a notification.
+    pub fn show(&self, records: Vec<BufferedRecord>) {
+        // The worker thread stops only when the logger is dropped.
+        let _ = self.sender.send(Job::Show(records));
+    }
+
+    /// Wait until all queued notifications are shown.
+    pub fn wait(&self, timeout: Duration) -> Result<()> {
+        let (sender, receiver) = mpsc::channel();
+        if self.sender.send(Job::Flush(sender)).is_err() {
+            return Ok(());
+        }
+        match receiver.recv_timeout(timeout) {
+            Err(mpsc::RecvTimeoutError::Timeout) => Err(Error::FlushTimeout(timeout)),
+            _ => Ok(()),
+        }
+    }
+
+    fn run<F>(receiver: &Receiver<Job>, show: F)
+    where
+        F: Fn(&[BufferedRecord]) -> bool,
+    {
+        while let Ok(job) = receiver.recv() {
+            match job {
+                Job::Show(records) => {
+                    // The panic is reported by the panic hook.
+                    // The records are lost, but the thread survives.
+                    let result = panic::catch_unwind(AssertUnwindSafe(|| show(&records)));
+                    if let Ok(false) = result {
+                        return;
+                    }
+                }
+                Job::Flush(sender) => {
+                    let _ = sender.send(());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    #[test]
+    fn wait() -> Result<()> {
+        let shown = Arc::new(Mutex::new(Vec::new()));
+        let shown_clone = shown.clone();
+        let worker = Worker::new(move |records| {
+            thread::sleep(Duration::from_millis(10));
+            shown_clone.lock().unwrap().push(records.len());
+            true
+        })?;
+        worker.show(vec![BufferedRecord::new(log::Level::Error, "", "1")]);
+        worker.show(Vec::new());
+        worker.wait(Duration::from_secs(5))?;
+        assert_eq!(*shown.lock().unwrap(), [1, 0]);
+        Ok(())
+    }
+
+    #[test]
+    fn panic() -> Result<()> {
+        let shown = Arc::new(Mutex::new(Vec::new()));
+        let shown_clone = shown.clone();
+        let worker = Worker::new(move |records| {
+            assert!(!records.is_empty(), "empty records");
+            shown_clone.lock().unwrap().push(records.len());
+            true
+        })?;
+        worker.show(Vec::new());
+        worker.show(vec![BufferedRecord::new(log::Level::Error, "", "1")]);
+        worker.wait(Duration::from_secs(5))?;
+        assert_eq!(*shown.lock().unwrap(), [1]);
+        Ok(())
+    }
+
+    #[test]
+    fn wait_timeout() -> Result<()> {
+        let worker = Worker::new(|_| {
+            thread::sleep(Duration::from_millis(500));
+            true
+        })?;
+        worker.show(Vec::new());
+        assert!(matches!(
+            worker.wait(Duration::from_millis(10)),
+            Err(Error::FlushTimeout(_))
+        ));
+        Ok(())
+    }
+}